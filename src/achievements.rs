@@ -0,0 +1,242 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_egui::{egui, EguiContext};
+use bevy_pkv::PkvStore;
+use bevy_yoleck::YoleckLevelIndex;
+
+use crate::door::handle_door_reached_events;
+use crate::game_clock::GameClock;
+use crate::global_types::{
+    AppState, ConnectionLost, GrabStatus, IsPlayer, IsZombie, LevelCompleted, LevelProgress,
+    MenuState,
+};
+use crate::level_info::LevelInfoCache;
+use crate::loading::GameAssets;
+use crate::playable_area::PlayableAreaBounds;
+use crate::utils::some_or;
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>();
+        app.init_resource::<AchievementToasts>();
+        app.add_startup_system(load_unlocked_achievements);
+        app.add_system_set(SystemSet::on_enter(AppState::LoadLevel).with_system(reset_run_stats));
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game)
+                .with_system(update_run_stats)
+                .with_system(
+                    check_achievements
+                        .after(update_run_stats)
+                        .after(handle_door_reached_events),
+                )
+        });
+        app.add_system(show_achievement_toasts);
+    }
+}
+
+/// What is known about the current attempt at the current level.
+#[derive(Default)]
+pub struct RunStats {
+    pub time: f32,
+    pub lost_connection: bool,
+    pub longest_drag: f32,
+    pub level_width: f32,
+    drag_start: Option<(Entity, Vec2)>,
+}
+
+pub struct AchievementContext<'a> {
+    pub level: &'a str,
+    pub run: &'a RunStats,
+    pub level_progress: &'a LevelProgress,
+    pub level_info_cache: &'a LevelInfoCache,
+    pub is_last_level: bool,
+}
+
+pub enum AchievementCheck {
+    OnLevelCompleted,
+    Continuously,
+}
+
+pub struct Achievement {
+    pub key: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub check: AchievementCheck,
+    pub condition: fn(&AchievementContext) -> bool,
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        key: "never_lost_connection",
+        title: "Stable Connection",
+        description: "Finish a level without ever losing connection",
+        check: AchievementCheck::OnLevelCompleted,
+        condition: |ctx| !ctx.run.lost_connection,
+    },
+    Achievement {
+        key: "balance_loader_par",
+        title: "Load Balanced",
+        description: "Finish Balance Loader under par time",
+        check: AchievementCheck::OnLevelCompleted,
+        condition: |ctx| {
+            ctx.level == "Balance_Loader.yol"
+                && ctx
+                    .level_info_cache
+                    .get(ctx.level)
+                    .and_then(|level_info| level_info.par_time)
+                    .map_or(false, |par_time| ctx.run.time <= par_time)
+        },
+    },
+    Achievement {
+        key: "long_drag",
+        title: "Dead Weight",
+        description: "Drag a zombie across the entire level",
+        check: AchievementCheck::Continuously,
+        condition: |ctx| {
+            0.0 < ctx.run.level_width && 0.8 * ctx.run.level_width <= ctx.run.longest_drag
+        },
+    },
+    Achievement {
+        key: "complete_the_game",
+        title: "Presentation Ready",
+        description: "Complete the game",
+        check: AchievementCheck::OnLevelCompleted,
+        condition: |ctx| ctx.is_last_level,
+    },
+];
+
+const ACHIEVEMENTS_PKV_KEY: &str = "achievements";
+
+#[derive(Default)]
+pub struct UnlockedAchievements(pub HashSet<String>);
+
+#[derive(Default)]
+struct AchievementToasts(VecDeque<(&'static Achievement, f32)>);
+
+fn load_unlocked_achievements(pkv: Res<PkvStore>, mut commands: Commands) {
+    let unlocked: Vec<String> = pkv.get(ACHIEVEMENTS_PKV_KEY).unwrap_or_default();
+    commands.insert_resource(UnlockedAchievements(unlocked.into_iter().collect()));
+}
+
+fn reset_run_stats(mut run_stats: ResMut<RunStats>) {
+    *run_stats = RunStats::default();
+}
+
+fn update_run_stats(
+    game_clock: Res<GameClock>,
+    mut run_stats: ResMut<RunStats>,
+    mut connection_lost_reader: EventReader<ConnectionLost>,
+    grabbers_query: Query<&GrabStatus, With<IsPlayer>>,
+    transforms_query: Query<&GlobalTransform>,
+    zombies_query: Query<(), With<IsZombie>>,
+    playable_area_query: Query<&PlayableAreaBounds>,
+) {
+    run_stats.time += game_clock.delta_seconds();
+    if connection_lost_reader.iter().next().is_some() {
+        run_stats.lost_connection = true;
+    }
+    if let Some(playable_area) = playable_area_query.iter().next() {
+        run_stats.level_width = playable_area.size.x.max(playable_area.size.y);
+    }
+    // Only dragging zombies counts - not props or portable routers.
+    let held = grabbers_query.iter().find_map(|grab_status| {
+        if let GrabStatus::Holding { other, .. } = grab_status {
+            zombies_query.get(*other).ok().map(|_| *other)
+        } else {
+            None
+        }
+    });
+    let held_position = held.and_then(|held| {
+        let transform = transforms_query.get(held).ok()?;
+        Some((held, transform.translation.truncate()))
+    });
+    run_stats.drag_start = match (run_stats.drag_start, held_position) {
+        (Some((start_entity, start_position)), Some((held, position))) if start_entity == held => {
+            let distance = start_position.distance(position);
+            run_stats.longest_drag = run_stats.longest_drag.max(distance);
+            Some((start_entity, start_position))
+        }
+        (_, held_position) => held_position,
+    };
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_achievements(
+    run_stats: Res<RunStats>,
+    level_progress: Res<LevelProgress>,
+    level_info_cache: Res<LevelInfoCache>,
+    game_assets: Res<GameAssets>,
+    level_index_assets: Res<Assets<YoleckLevelIndex>>,
+    mut level_completed_reader: EventReader<LevelCompleted>,
+    unlocked: Option<ResMut<UnlockedAchievements>>,
+    mut toasts: ResMut<AchievementToasts>,
+    mut pkv: ResMut<PkvStore>,
+) {
+    let mut unlocked = some_or!(unlocked; return);
+    let level = some_or!(
+        level_progress.current_level.as_ref();
+        return // achievements are not tracked inside the editor
+    );
+    let level_completed = level_completed_reader.iter().next().is_some();
+    let is_last_level = level_index_assets
+        .get(&game_assets.level_index)
+        .and_then(|level_index| level_index.iter().last())
+        .map_or(false, |last_level| last_level.filename == *level);
+    let ctx = AchievementContext {
+        level,
+        run: &run_stats,
+        level_progress: &level_progress,
+        level_info_cache: &level_info_cache,
+        is_last_level: level_completed && is_last_level,
+    };
+    let mut newly_unlocked = false;
+    for achievement in ACHIEVEMENTS {
+        if unlocked.0.contains(achievement.key) {
+            continue;
+        }
+        let should_check = match achievement.check {
+            AchievementCheck::OnLevelCompleted => level_completed,
+            AchievementCheck::Continuously => true,
+        };
+        if should_check && (achievement.condition)(&ctx) {
+            unlocked.0.insert(achievement.key.to_owned());
+            toasts.0.push_back((achievement, 3.0));
+            newly_unlocked = true;
+        }
+    }
+    if newly_unlocked {
+        let keys: Vec<&String> = unlocked.0.iter().collect();
+        if let Err(err) = pkv.set(ACHIEVEMENTS_PKV_KEY, &keys) {
+            error!("Cannot save achievements: {}", err);
+        }
+    }
+}
+
+fn show_achievement_toasts(
+    time: Res<Time>,
+    mut egui_context: ResMut<EguiContext>,
+    mut toasts: ResMut<AchievementToasts>,
+    state: Res<State<AppState>>,
+) {
+    if let AppState::Menu(MenuState::Achievements) = state.current() {
+        return;
+    }
+    let (achievement, time_left) = some_or!(toasts.0.front_mut(); return);
+    *time_left -= time.delta_seconds();
+    if *time_left <= 0.0 {
+        toasts.0.pop_front();
+        return;
+    }
+    egui::Area::new("achievement-toast")
+        .anchor(egui::Align2::RIGHT_TOP, [-8.0, 8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(egui::RichText::new("Achievement Unlocked!").strong());
+                ui.label(achievement.title);
+            });
+        });
+}
@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
+use bevy_yoleck::{
+    egui, YoleckEdit, YoleckExtForApp, YoleckManaged, YoleckPopulate, YoleckTypeHandler,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::global_types::{AppState, Grabbable, SignalBeam, WifiRouter};
+
+pub struct AntennaPlugin;
+
+impl Plugin for AntennaPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<Antenna>::new("Antenna")
+                .populate_with(populate)
+                .with(yoleck_vpeol_position_edit_adapter(
+                    |antenna: &mut Antenna| YoleckVpeolTransform2dProjection {
+                        translation: &mut antenna.position,
+                    },
+                ))
+                .edit_with(edit)
+        });
+        app.add_system_set(SystemSet::on_update(AppState::Game).with_system(aim_routers));
+    }
+}
+
+/// Turning the antenna turns the beam of the router it's connected to.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Antenna {
+    #[serde(default)]
+    position: Vec2,
+    /// The direction the antenna points to, counter-clockwise from the X axis.
+    #[serde(default)]
+    rotation: f32,
+    /// The Yoleck name of the router.
+    #[serde(default)]
+    router: String,
+}
+
+#[derive(Component)]
+struct AntennaStatus {
+    router: String,
+}
+
+fn populate(mut populate: YoleckPopulate<Antenna>) {
+    populate.populate(|_ctx, data, mut cmd| {
+        cmd.insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::ORANGE,
+                custom_size: Some(Vec2::new(0.8, 0.2)),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        cmd.insert_bundle(TransformBundle::from_transform(
+            Transform::from_translation(data.position.extend(0.0))
+                .with_rotation(Quat::from_rotation_z(data.rotation)),
+        ));
+        cmd.insert(AntennaStatus {
+            router: data.router.clone(),
+        });
+        // Can only be turned, not moved.
+        cmd.insert(RigidBody::Dynamic);
+        cmd.insert(LockedAxes::TRANSLATION_LOCKED);
+        cmd.insert(Damping {
+            linear_damping: 1.0,
+            angular_damping: 5.0,
+        });
+        cmd.insert(Collider::cuboid(0.4, 0.1));
+        cmd.insert(ColliderMassProperties::Density(5.0));
+        cmd.insert(Velocity::default());
+        cmd.insert(Grabbable::default());
+    });
+}
+
+fn edit(mut edit: YoleckEdit<Antenna>) {
+    edit.edit(|_, data, ui| {
+        use std::f32::consts::{FRAC_PI_8, PI};
+        ui.horizontal(|ui| {
+            ui.label("Router:");
+            ui.text_edit_singleline(&mut data.router);
+        });
+        ui.add({
+            egui::Slider::new(&mut data.rotation, PI..=-PI)
+                .prefix("Angle: ")
+                .step_by(FRAC_PI_8 as f64)
+        });
+    });
+}
+
+fn aim_routers(
+    antennas_query: Query<(&GlobalTransform, &AntennaStatus)>,
+    mut routers_query: Query<(&YoleckManaged, &mut WifiRouter)>,
+) {
+    for (antenna_transform, antenna_status) in antennas_query.iter() {
+        let forward = antenna_transform.rotation * Vec3::X;
+        let direction = forward.y.atan2(forward.x);
+        for (yoleck_managed, mut wifi_router) in routers_query.iter_mut() {
+            if yoleck_managed.name != antenna_status.router {
+                continue;
+            }
+            // Checked first so that the router is only marked as changed when it really is.
+            if let Some(beam) = wifi_router.beam {
+                if beam.direction != direction {
+                    wifi_router.beam = Some(SignalBeam { direction, ..beam });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::audio::AudioSink;
+use bevy::prelude::*;
+use bevy_asset_loader::{AssetCollection, AssetCollectionApp};
+use bevy_pkv::PkvStore;
+use serde::{Deserialize, Serialize};
+
+use crate::global_types::{
+    AppState, DoorOpened, DownloadCompleted, DownloadProgress, GrabReleased, GrabStarted, IsPlayer,
+    WifiClient,
+};
+use crate::utils::some_or;
+
+/// Bevy's audio output already turns playback into a no-op when there is no audio device, so
+/// nothing here needs to check for one.
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_collection::<AudioAssets>();
+        app.init_resource::<AudioSinks>();
+        app.add_startup_system(load_audio_settings);
+        app.add_system(play_sound_effects);
+        app.add_system(control_connection_alarm);
+        app.add_system(control_background_music);
+    }
+}
+
+#[derive(AssetCollection)]
+struct AudioAssets {
+    #[asset(path = "sounds/grab.wav")]
+    grab: Handle<AudioSource>,
+    #[asset(path = "sounds/release.wav")]
+    release: Handle<AudioSource>,
+    #[asset(path = "sounds/door.wav")]
+    door: Handle<AudioSource>,
+    #[asset(path = "sounds/alarm.wav")]
+    alarm: Handle<AudioSource>,
+    #[asset(path = "sounds/jingle.wav")]
+    jingle: Handle<AudioSource>,
+    #[asset(path = "sounds/music.wav")]
+    music: Handle<AudioSource>,
+}
+
+const AUDIO_SETTINGS_PKV_KEY: &str = "audio_settings";
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioSettings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master_volume: 0.8,
+            music_volume: 0.5,
+            effects_volume: 0.8,
+        }
+    }
+}
+
+impl AudioSettings {
+    pub fn save(&self, pkv: &mut PkvStore) {
+        if let Err(err) = pkv.set(AUDIO_SETTINGS_PKV_KEY, self) {
+            error!("Cannot save audio settings: {}", err);
+        }
+    }
+
+    fn effects(&self) -> f32 {
+        self.master_volume * self.effects_volume
+    }
+
+    fn music(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+}
+
+#[derive(Default)]
+struct AudioSinks {
+    alarm: Option<Handle<AudioSink>>,
+    music: Option<Handle<AudioSink>>,
+}
+
+fn load_audio_settings(pkv: Res<PkvStore>, mut commands: Commands) {
+    commands.insert_resource(
+        pkv.get::<AudioSettings>(AUDIO_SETTINGS_PKV_KEY)
+            .unwrap_or_default(),
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play_sound_effects(
+    audio: Res<Audio>,
+    audio_assets: Res<AudioAssets>,
+    audio_settings: Option<Res<AudioSettings>>,
+    mut grab_started_reader: EventReader<GrabStarted>,
+    mut grab_released_reader: EventReader<GrabReleased>,
+    mut door_opened_reader: EventReader<DoorOpened>,
+    mut download_completed_reader: EventReader<DownloadCompleted>,
+) {
+    let volume = audio_settings.map_or(0.0, |audio_settings| audio_settings.effects());
+    let play = |sound: &Handle<AudioSource>| {
+        audio.play_with_settings(sound.clone(), PlaybackSettings::ONCE.with_volume(volume));
+    };
+    for _ in grab_started_reader.iter() {
+        play(&audio_assets.grab);
+    }
+    for _ in grab_released_reader.iter() {
+        play(&audio_assets.release);
+    }
+    for _ in door_opened_reader.iter() {
+        play(&audio_assets.door);
+    }
+    for _ in download_completed_reader.iter() {
+        play(&audio_assets.jingle);
+    }
+}
+
+fn control_connection_alarm(
+    state: Res<State<AppState>>,
+    audio: Res<Audio>,
+    audio_assets: Res<AudioAssets>,
+    audio_settings: Option<Res<AudioSettings>>,
+    mut audio_sinks: ResMut<AudioSinks>,
+    sinks: Res<Assets<AudioSink>>,
+    player_query: Query<&DownloadProgress, With<IsPlayer>>,
+) {
+    let handle = audio_sinks.alarm.get_or_insert_with(|| {
+        sinks.get_handle(audio.play_with_settings(
+            audio_assets.alarm.clone(),
+            PlaybackSettings::LOOP.with_volume(0.0),
+        ))
+    });
+    let sink = some_or!(sinks.get(&*handle); return);
+    let losing_connection = *state.current() == AppState::Game
+        && player_query
+            .iter()
+            .any(|progress| matches!(progress, DownloadProgress::LosingConnection { .. }));
+    if losing_connection {
+        sink.set_volume(audio_settings.map_or(0.0, |audio_settings| audio_settings.effects()));
+        sink.play();
+    } else {
+        sink.pause();
+    }
+}
+
+fn control_background_music(
+    state: Res<State<AppState>>,
+    audio: Res<Audio>,
+    audio_assets: Res<AudioAssets>,
+    audio_settings: Option<Res<AudioSettings>>,
+    mut audio_sinks: ResMut<AudioSinks>,
+    sinks: Res<Assets<AudioSink>>,
+    player_query: Query<&WifiClient, With<IsPlayer>>,
+) {
+    let handle = audio_sinks.music.get_or_insert_with(|| {
+        sinks.get_handle(audio.play_with_settings(
+            audio_assets.music.clone(),
+            PlaybackSettings::LOOP.with_volume(0.0),
+        ))
+    });
+    let sink = some_or!(sinks.get(&*handle); return);
+    let volume = audio_settings.map_or(0.0, |audio_settings| audio_settings.music());
+    // The music fades out together with the signal, to make the player feel the connection.
+    let signal_factor = if *state.current() == AppState::Game {
+        player_query
+            .iter()
+            .map(|wifi_client| wifi_client.signal_strength)
+            .reduce(f32::max)
+            .map_or(1.0, |signal_strength| 0.2 + 0.8 * signal_strength)
+    } else {
+        1.0
+    };
+    sink.set_volume(volume * signal_factor);
+}
@@ -3,7 +3,8 @@ use bevy::text::Text2dSize;
 use bevy_egui::EguiSettings;
 use bevy_yoleck::YoleckEditorState;
 
-use crate::global_types::CameraInclude;
+use crate::global_types::{AppState, CameraInclude, MenuState};
+use crate::level_info::LevelThumbnail;
 use crate::utils::some_or;
 
 pub struct CameraPlugin {
@@ -41,33 +42,49 @@ fn update_camera_transform(
         (&GlobalTransform, AnyOf<(&Sprite, &Text2dSize)>),
         With<CameraInclude>,
     >,
+    thumbnails_query: Query<(&GlobalTransform, &LevelThumbnail)>,
+    state: Option<Res<State<AppState>>>,
     mut egui_settings: ResMut<EguiSettings>,
 ) {
     let mut minmax: Option<[f32; 4]> = None;
-    for (global_transform, (sprite, text_2d_size)) in camera_included_objects_query.iter() {
-        let (vec_to_min, vec_to_max) = if let Some(sprite) = sprite {
-            let vec = 0.5 * sprite.custom_size.unwrap().extend(0.0);
-            (-vec, vec)
-        } else if let Some(text_2d_size) = text_2d_size {
-            (
-                Vec3::ZERO,
-                Vec3::new(text_2d_size.size.width, text_2d_size.size.height, 0.0),
-            )
-        } else {
-            panic!("No option for calculating the size");
-        };
-        let min_corner = global_transform.mul_vec3(vec_to_min);
-        let max_corner = global_transform.mul_vec3(vec_to_max);
-        minmax = if let Some([l, b, r, t]) = minmax {
-            Some([
-                l.min(min_corner.x),
-                b.min(min_corner.y),
-                r.max(max_corner.x),
-                t.max(max_corner.y),
-            ])
-        } else {
-            Some([min_corner.x, min_corner.y, max_corner.x, max_corner.y])
-        };
+    if state.map_or(false, |state| {
+        *state.current() == AppState::Menu(MenuState::LevelSplash)
+    }) {
+        for (global_transform, thumbnail) in thumbnails_query.iter() {
+            if thumbnail.size.x <= 0.0 || thumbnail.size.y <= 0.0 {
+                continue;
+            }
+            let min_corner = global_transform.translation.truncate() - 0.5 * thumbnail.size;
+            let max_corner = global_transform.translation.truncate() + 0.5 * thumbnail.size;
+            minmax = Some([min_corner.x, min_corner.y, max_corner.x, max_corner.y]);
+        }
+    }
+    if minmax.is_none() {
+        for (global_transform, (sprite, text_2d_size)) in camera_included_objects_query.iter() {
+            let (vec_to_min, vec_to_max) = if let Some(sprite) = sprite {
+                let vec = 0.5 * sprite.custom_size.unwrap().extend(0.0);
+                (-vec, vec)
+            } else if let Some(text_2d_size) = text_2d_size {
+                (
+                    Vec3::ZERO,
+                    Vec3::new(text_2d_size.size.width, text_2d_size.size.height, 0.0),
+                )
+            } else {
+                panic!("No option for calculating the size");
+            };
+            let min_corner = global_transform.mul_vec3(vec_to_min);
+            let max_corner = global_transform.mul_vec3(vec_to_max);
+            minmax = if let Some([l, b, r, t]) = minmax {
+                Some([
+                    l.min(min_corner.x),
+                    b.min(min_corner.y),
+                    r.max(max_corner.x),
+                    t.max(max_corner.y),
+                ])
+            } else {
+                Some([min_corner.x, min_corner.y, max_corner.x, max_corner.y])
+            };
+        }
     }
     let minmax = some_or!(minmax; return);
     let world_width = minmax[2] - minmax[0];
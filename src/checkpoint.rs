@@ -0,0 +1,253 @@
+use bevy::prelude::*;
+use bevy_rapier2d::pipeline::CollisionEvent;
+use bevy_rapier2d::prelude::{ActiveEvents, Collider, Sensor, Velocity};
+use bevy_rapier2d::rapier::prelude::CollisionEventFlags;
+use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
+use bevy_yoleck::{egui, YoleckEdit, YoleckExtForApp, YoleckPopulate, YoleckTypeHandler};
+use serde::{Deserialize, Serialize};
+
+use crate::global_types::{
+    AppState, DoorStatus, DownloadProgress, GrabReleased, GrabStatus, IsPlayer, IsZombie,
+    SpawnedDuringLevel, WifiRouter,
+};
+use crate::patrol_path::PatrolPathFollower;
+use crate::utils::{entities_ordered_by_type, some_or};
+
+pub struct CheckpointPlugin;
+
+impl Plugin for CheckpointPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<Checkpoint>::new("Checkpoint")
+                .populate_with(populate)
+                .with(yoleck_vpeol_position_edit_adapter(
+                    |checkpoint: &mut Checkpoint| YoleckVpeolTransform2dProjection {
+                        translation: &mut checkpoint.position,
+                    },
+                ))
+                .edit_with(edit)
+        });
+        app.add_event::<RestoreCheckpoint>();
+        app.add_system_set(
+            SystemSet::on_update(AppState::Game).with_system(handle_checkpoint_reached_events),
+        );
+        app.add_system(restore_checkpoint);
+        app.add_system_set(SystemSet::on_enter(AppState::LoadLevel).with_system(
+            |mut commands: Commands| commands.remove_resource::<CheckpointSnapshot>(),
+        ));
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    #[serde(default)]
+    position: Vec2,
+    #[serde(default = "default_size")]
+    size: Vec2,
+}
+
+fn default_size() -> Vec2 {
+    Vec2::new(1.0, 1.0)
+}
+
+#[derive(Component)]
+struct CheckpointStatus {
+    is_reached: bool,
+}
+
+const CHECKPOINT_COLOR: Color = Color::rgba(0.0, 0.6, 1.0, 0.3);
+const REACHED_CHECKPOINT_COLOR: Color = Color::rgba(0.0, 1.0, 0.4, 0.4);
+
+/// The state of the level when the last checkpoint was reached. Exists as a resource only after
+/// reaching a checkpoint.
+pub struct CheckpointSnapshot {
+    bodies: Vec<(Entity, Transform, Velocity)>,
+    download_progresses: Vec<(Entity, DownloadProgress)>,
+    routers: Vec<(Entity, bool)>,
+    doors: Vec<(Entity, bool, bool)>,
+    /// Patrolling entities are not bodies, but still need to be back where they were.
+    patrols: Vec<(Entity, Transform, PatrolPathFollower)>,
+}
+
+/// Send this to bring the level back to the last snapshot.
+pub struct RestoreCheckpoint;
+
+fn populate(mut populate: YoleckPopulate<Checkpoint>) {
+    populate.populate(|_ctx, data, mut cmd| {
+        cmd.insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: CHECKPOINT_COLOR,
+                custom_size: Some(data.size),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(data.position.extend(-0.2)),
+            ..Default::default()
+        });
+        cmd.insert(CheckpointStatus { is_reached: false });
+        cmd.insert(Collider::cuboid(0.5 * data.size.x, 0.5 * data.size.y));
+        cmd.insert(Sensor(true));
+        cmd.insert(ActiveEvents::COLLISION_EVENTS);
+    });
+}
+
+fn edit(mut edit: YoleckEdit<Checkpoint>) {
+    edit.edit(|_ctx, data, ui| {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut data.size.x)
+                    .prefix("Width:")
+                    .speed(0.05),
+            );
+            ui.add(
+                egui::DragValue::new(&mut data.size.y)
+                    .prefix("Height:")
+                    .speed(0.05),
+            );
+        });
+    });
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn handle_checkpoint_reached_events(
+    mut reader: EventReader<CollisionEvent>,
+    player_query: Query<(), With<IsPlayer>>,
+    mut checkpoints_query: Query<(&mut CheckpointStatus, &mut Sprite)>,
+    bodies_query: Query<(Entity, &Transform, &Velocity), Or<(With<IsPlayer>, With<IsZombie>)>>,
+    download_progress_query: Query<(Entity, &DownloadProgress)>,
+    routers_query: Query<(Entity, &WifiRouter)>,
+    doors_query: Query<(Entity, &DoorStatus)>,
+    patrols_query: Query<(Entity, &Transform, &PatrolPathFollower)>,
+    mut commands: Commands,
+) {
+    for event in reader.iter() {
+        let [_player_entity, checkpoint_entity] = match event {
+            CollisionEvent::Started(entity1, entity2, flags) => {
+                if flags.contains(CollisionEventFlags::SENSOR) {
+                    if let Some(entities) = entities_ordered_by_type!(
+                        [*entity1, *entity2],
+                        player_query,
+                        checkpoints_query,
+                    ) {
+                        entities
+                    } else {
+                        continue;
+                    }
+                } else {
+                    continue;
+                }
+            }
+            CollisionEvent::Stopped(_, _, _) => {
+                continue;
+            }
+        };
+        let (mut checkpoint_status, mut sprite) =
+            checkpoints_query.get_mut(checkpoint_entity).unwrap();
+        if checkpoint_status.is_reached {
+            continue;
+        }
+        checkpoint_status.is_reached = true;
+        sprite.color = REACHED_CHECKPOINT_COLOR;
+        commands.insert_resource(CheckpointSnapshot {
+            bodies: bodies_query
+                .iter()
+                .map(|(entity, transform, velocity)| (entity, *transform, *velocity))
+                .collect(),
+            download_progresses: download_progress_query
+                .iter()
+                .map(|(entity, download_progress)| (entity, download_progress.clone()))
+                .collect(),
+            routers: routers_query
+                .iter()
+                .map(|(entity, wifi_router)| (entity, wifi_router.is_online))
+                .collect(),
+            doors: doors_query
+                .iter()
+                .map(|(entity, door_status)| {
+                    (entity, door_status.is_open, door_status.is_forced_open)
+                })
+                .collect(),
+            patrols: patrols_query
+                .iter()
+                .map(|(entity, transform, follower)| (entity, *transform, follower.clone()))
+                .collect(),
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn restore_checkpoint(
+    mut reader: EventReader<RestoreCheckpoint>,
+    snapshot: Option<Res<CheckpointSnapshot>>,
+    mut bodies_query: Query<(&mut Transform, &mut Velocity)>,
+    spawned_zombies_query: Query<Entity, (With<IsZombie>, With<SpawnedDuringLevel>)>,
+    mut download_progress_query: Query<&mut DownloadProgress>,
+    mut routers_query: Query<&mut WifiRouter>,
+    mut doors_query: Query<&mut DoorStatus>,
+    mut patrols_query: Query<(&mut Transform, &mut PatrolPathFollower), Without<Velocity>>,
+    mut grabbers_query: Query<(Entity, &mut GrabStatus)>,
+    mut commands: Commands,
+    mut grab_released_writer: EventWriter<GrabReleased>,
+) {
+    if reader.iter().next().is_none() {
+        return;
+    }
+    let snapshot = some_or!(snapshot; return);
+    for (entity, transform, velocity) in snapshot.bodies.iter() {
+        if let Ok((mut current_transform, mut current_velocity)) = bodies_query.get_mut(*entity) {
+            *current_transform = *transform;
+            *current_velocity = *velocity;
+        }
+    }
+    // Zombies spawned after the checkpoint was reached are not part of the snapshot.
+    for zombie_entity in spawned_zombies_query.iter() {
+        if !snapshot
+            .bodies
+            .iter()
+            .any(|(entity, _, _)| *entity == zombie_entity)
+        {
+            commands.entity(zombie_entity).despawn_recursive();
+        }
+    }
+    for (entity, download_progress) in snapshot.download_progresses.iter() {
+        if let Ok(mut current_download_progress) = download_progress_query.get_mut(*entity) {
+            *current_download_progress = download_progress.clone();
+        }
+    }
+    for (entity, is_online) in snapshot.routers.iter() {
+        if let Ok(mut wifi_router) = routers_query.get_mut(*entity) {
+            wifi_router.is_online = *is_online;
+        }
+    }
+    for (entity, is_open, is_forced_open) in snapshot.doors.iter() {
+        if let Ok(mut door_status) = doors_query.get_mut(*entity) {
+            door_status.is_open = *is_open;
+            door_status.is_forced_open = *is_forced_open;
+        }
+    }
+    for (entity, transform, follower) in snapshot.patrols.iter() {
+        if let Ok((mut current_transform, mut current_follower)) = patrols_query.get_mut(*entity) {
+            *current_transform = *transform;
+            *current_follower = follower.clone();
+        }
+    }
+    // Holding on to something through a restore would drag it across the level.
+    for (grabber_entity, mut grab_status) in grabbers_query.iter_mut() {
+        match *grab_status {
+            GrabStatus::NoGrab | GrabStatus::GrabFailed => {}
+            GrabStatus::Reaching { hands_entity, .. } => {
+                commands.entity(hands_entity).despawn_recursive();
+            }
+            GrabStatus::Holding {
+                hands_entity,
+                other,
+            } => {
+                commands.entity(hands_entity).despawn_recursive();
+                grab_released_writer.send(GrabReleased {
+                    grabber: grabber_entity,
+                    grabbed: other,
+                });
+            }
+        }
+        *grab_status = GrabStatus::NoGrab;
+    }
+}
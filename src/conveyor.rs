@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_rapier2d::pipeline::CollisionEvent;
+use bevy_rapier2d::prelude::{ActiveEvents, Collider, Sensor, Velocity};
+use bevy_rapier2d::rapier::prelude::CollisionEventFlags;
+use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
+use bevy_yoleck::{egui, YoleckEdit, YoleckExtForApp, YoleckPopulate, YoleckTypeHandler};
+use serde::{Deserialize, Serialize};
+
+use crate::game_clock::GameClock;
+use crate::global_types::{AppState, GameSystemLabel};
+use crate::movement_resolver::MoveController;
+use crate::utils::entities_ordered_by_type;
+
+pub struct ConveyorPlugin;
+
+impl Plugin for ConveyorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<Conveyor>::new("Conveyor")
+                .populate_with(populate)
+                .with(yoleck_vpeol_position_edit_adapter(
+                    |conveyor: &mut Conveyor| YoleckVpeolTransform2dProjection {
+                        translation: &mut conveyor.position,
+                    },
+                ))
+                .edit_with(edit)
+        });
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game)
+                .with_system(track_bodies_on_conveyors)
+                .with_system(
+                    carry_bodies
+                        .after(track_bodies_on_conveyors)
+                        .before(GameSystemLabel::ApplyMovement),
+                )
+                .with_system(animate_conveyor_stripes)
+        });
+        app.add_system_set(
+            SystemSet::on_enter(AppState::LoadLevel).with_system(clear_bodies_on_conveyors),
+        );
+        app.add_system(update_conveyor_sprites);
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conveyor {
+    #[serde(default)]
+    position: Vec2,
+    /// The belt moves along `size.x`.
+    #[serde(default = "default_size")]
+    size: Vec2,
+    #[serde(default)]
+    rotation: f32,
+    #[serde(default = "default_speed")]
+    speed: f32,
+    #[serde(default = "default_is_on")]
+    is_on: bool,
+}
+
+fn default_size() -> Vec2 {
+    Vec2::new(4.0, 1.0)
+}
+
+fn default_speed() -> f32 {
+    3.0
+}
+
+fn default_is_on() -> bool {
+    true
+}
+
+#[derive(Component)]
+pub struct ConveyorStatus {
+    pub is_on: bool,
+    velocity: Vec2,
+    bodies_on: HashSet<Entity>,
+}
+
+#[derive(Component)]
+struct ConveyorStripe {
+    /// Stripes wrap around when they get to the end of the belt.
+    belt_length: f32,
+    speed: f32,
+}
+
+/// How fast bodies that don't move by themselves get up to the belt's speed.
+const CONVEYOR_ACCELERATION: f32 = 20.0;
+
+const STRIPE_SPACING: f32 = 0.5;
+
+fn populate(mut populate: YoleckPopulate<Conveyor>) {
+    populate.populate(|_ctx, data, mut cmd| {
+        cmd.despawn_descendants();
+        cmd.insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgb(0.25, 0.25, 0.3),
+                custom_size: Some(data.size),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(data.position.extend(-0.3))
+                .with_rotation(Quat::from_rotation_z(data.rotation)),
+            ..Default::default()
+        });
+        let direction = Vec2::new(data.rotation.cos(), data.rotation.sin());
+        cmd.insert(ConveyorStatus {
+            is_on: data.is_on,
+            velocity: data.speed * direction,
+            bodies_on: Default::default(),
+        });
+        cmd.insert(Collider::cuboid(0.5 * data.size.x, 0.5 * data.size.y));
+        cmd.insert(Sensor(true));
+        cmd.insert(ActiveEvents::COLLISION_EVENTS);
+
+        let num_stripes = (data.size.x / STRIPE_SPACING).floor() as usize;
+        let belt_length = num_stripes as f32 * STRIPE_SPACING;
+        cmd.with_children(|commands| {
+            for i in 0..num_stripes {
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgb(0.45, 0.45, 0.5),
+                            custom_size: Some(Vec2::new(0.08, 0.8 * data.size.y)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(
+                            (i as f32 + 0.5) * STRIPE_SPACING - 0.5 * belt_length,
+                            0.0,
+                            0.01,
+                        ),
+                        ..Default::default()
+                    })
+                    .insert(ConveyorStripe {
+                        belt_length,
+                        speed: data.speed,
+                    });
+            }
+        });
+    });
+}
+
+fn edit(mut edit: YoleckEdit<Conveyor>) {
+    edit.edit(|_ctx, data, ui| {
+        use std::f32::consts::{FRAC_PI_8, PI};
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut data.size.x)
+                    .prefix("Length:")
+                    .speed(0.05),
+            );
+            ui.add(
+                egui::DragValue::new(&mut data.size.y)
+                    .prefix("Width:")
+                    .speed(0.05),
+            );
+        });
+        ui.add({
+            egui::Slider::new(&mut data.rotation, PI..=-PI)
+                .prefix("Direction: ")
+                .step_by(FRAC_PI_8 as f64)
+        });
+        ui.add(egui::Slider::new(&mut data.speed, 0.5..=10.0).prefix("Speed: "));
+        ui.checkbox(&mut data.is_on, "Starts On");
+    });
+}
+
+fn track_bodies_on_conveyors(
+    mut reader: EventReader<CollisionEvent>,
+    bodies_query: Query<(), With<Velocity>>,
+    mut conveyors_query: Query<&mut ConveyorStatus>,
+) {
+    // Bodies can be despawned while on a conveyor, without a collision event to say so.
+    for mut conveyor_status in conveyors_query.iter_mut() {
+        conveyor_status
+            .bodies_on
+            .retain(|body_entity| bodies_query.get(*body_entity).is_ok());
+    }
+    for event in reader.iter() {
+        let (entity1, entity2, flags, is_started) = match event {
+            CollisionEvent::Started(entity1, entity2, flags) => (entity1, entity2, flags, true),
+            CollisionEvent::Stopped(entity1, entity2, flags) => (entity1, entity2, flags, false),
+        };
+        if !flags.contains(CollisionEventFlags::SENSOR) {
+            continue;
+        }
+        let [body_entity, conveyor_entity] = if let Some(entities) =
+            entities_ordered_by_type!([*entity1, *entity2], bodies_query, conveyors_query)
+        {
+            entities
+        } else {
+            continue;
+        };
+        let mut conveyor_status = conveyors_query.get_mut(conveyor_entity).unwrap();
+        if is_started {
+            conveyor_status.bodies_on.insert(body_entity);
+        } else {
+            conveyor_status.bodies_on.remove(&body_entity);
+        }
+    }
+}
+
+fn clear_bodies_on_conveyors(mut conveyors_query: Query<&mut ConveyorStatus>) {
+    for mut conveyor_status in conveyors_query.iter_mut() {
+        conveyor_status.bodies_on.clear();
+    }
+}
+
+/// Bodies with a `MoveController` get the belt's velocity as a base for their own movement. Other
+/// bodies get pushed along until they match the belt's speed.
+fn carry_bodies(
+    game_clock: Res<GameClock>,
+    conveyors_query: Query<&ConveyorStatus>,
+    mut bodies_query: Query<(Entity, &mut Velocity, Option<&mut MoveController>)>,
+) {
+    for (body_entity, mut velocity, move_controller) in bodies_query.iter_mut() {
+        let carried_velocity: Vec2 = conveyors_query
+            .iter()
+            .filter(|conveyor_status| {
+                conveyor_status.is_on && conveyor_status.bodies_on.contains(&body_entity)
+            })
+            .fold(Vec2::ZERO, |sum, conveyor_status| {
+                sum + conveyor_status.velocity
+            });
+        if let Some(mut move_controller) = move_controller {
+            move_controller.carried_velocity = carried_velocity;
+            continue;
+        }
+        if carried_velocity == Vec2::ZERO {
+            continue;
+        }
+        let direction = carried_velocity.normalize();
+        let missing_speed = carried_velocity.length() - velocity.linvel.dot(direction);
+        if 0.0 < missing_speed {
+            velocity.linvel +=
+                direction * missing_speed.min(CONVEYOR_ACCELERATION * game_clock.delta_seconds());
+        }
+    }
+}
+
+fn animate_conveyor_stripes(
+    game_clock: Res<GameClock>,
+    conveyors_query: Query<(&ConveyorStatus, &Children)>,
+    mut stripes_query: Query<(&ConveyorStripe, &mut Transform)>,
+) {
+    for (conveyor_status, children) in conveyors_query.iter() {
+        if !conveyor_status.is_on {
+            continue;
+        }
+        for child in children.iter() {
+            if let Ok((stripe, mut transform)) = stripes_query.get_mut(*child) {
+                let half_length = 0.5 * stripe.belt_length;
+                transform.translation.x = (transform.translation.x
+                    + stripe.speed * game_clock.delta_seconds()
+                    + half_length)
+                    .rem_euclid(stripe.belt_length)
+                    - half_length;
+            }
+        }
+    }
+}
+
+fn update_conveyor_sprites(
+    mut query: Query<(&ConveyorStatus, &mut Sprite), Changed<ConveyorStatus>>,
+) {
+    for (conveyor_status, mut sprite) in query.iter_mut() {
+        sprite.color = if conveyor_status.is_on {
+            Color::rgb(0.25, 0.25, 0.3)
+        } else {
+            Color::rgb(0.15, 0.15, 0.15)
+        };
+    }
+}
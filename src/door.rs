@@ -6,7 +6,9 @@ use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTrans
 use bevy_yoleck::{YoleckExtForApp, YoleckPopulate, YoleckTypeHandler};
 use serde::{Deserialize, Serialize};
 
-use crate::global_types::{AppState, CameraInclude, DoorStatus, DownloadProgress, IsPlayer};
+use crate::global_types::{
+    AppState, CameraInclude, DoorOpened, DoorStatus, DownloadProgress, IsPlayer, LevelCompleted,
+};
 use crate::loading::GameAssets;
 use crate::utils::entities_ordered_by_type;
 
@@ -23,20 +25,28 @@ impl Plugin for DoorPlugin {
                     }
                 }))
         });
-        app.add_system_set(SystemSet::on_update(AppState::Game).with_system(update_doors_status));
-        app.add_system(handle_door_reached_events);
+        app.add_event::<DoorOpened>();
+        app.add_event::<LevelCompleted>();
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game)
+                .with_system(update_doors_status)
+                .with_system(handle_door_reached_events)
+        });
     }
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
-struct Door {
+pub struct Door {
     #[serde(default)]
-    position: Vec2,
+    pub(crate) position: Vec2,
 }
 
 fn populate(mut populate: YoleckPopulate<Door>, game_assets: Res<GameAssets>) {
     populate.populate(|_, data, mut cmd| {
-        cmd.insert(DoorStatus { is_open: false });
+        cmd.insert(DoorStatus {
+            is_open: false,
+            is_forced_open: false,
+        });
         cmd.insert_bundle(SpriteSheetBundle {
             sprite: TextureAtlasSprite {
                 index: 0,
@@ -58,22 +68,28 @@ fn populate(mut populate: YoleckPopulate<Door>, game_assets: Res<GameAssets>) {
 
 fn update_doors_status(
     downloads_query: Query<&DownloadProgress>,
-    mut doors_query: Query<(&mut DoorStatus, &mut TextureAtlasSprite)>,
+    mut doors_query: Query<(Entity, &mut DoorStatus, &mut TextureAtlasSprite)>,
+    mut door_opened_writer: EventWriter<DoorOpened>,
 ) {
     let should_be_open = downloads_query
         .iter()
         .any(|progress| matches!(progress, DownloadProgress::Completed));
-    for (mut door_status, mut sprite) in doors_query.iter_mut() {
-        door_status.is_open = should_be_open;
-        sprite.index = if should_be_open { 1 } else { 0 };
+    for (door_entity, mut door_status, mut sprite) in doors_query.iter_mut() {
+        let was_open = door_status.is_open;
+        door_status.is_open = should_be_open || door_status.is_forced_open;
+        if door_status.is_open && !was_open {
+            door_opened_writer.send(DoorOpened { door: door_entity });
+        }
+        sprite.index = if door_status.is_open { 1 } else { 0 };
     }
 }
 
-fn handle_door_reached_events(
+pub fn handle_door_reached_events(
     mut reader: EventReader<CollisionEvent>,
     player_query: Query<(), With<IsPlayer>>,
     door_query: Query<&DoorStatus>,
     mut state: ResMut<State<AppState>>,
+    mut level_completed_writer: EventWriter<LevelCompleted>,
 ) {
     for event in reader.iter() {
         let [_player_entity, door_entity] = match event {
@@ -96,6 +112,7 @@ fn handle_door_reached_events(
         };
         let door_status = door_query.get(door_entity).unwrap();
         if door_status.is_open {
+            level_completed_writer.send(LevelCompleted { door: door_entity });
             state.set(AppState::LevelCompleted).unwrap();
             return;
         }
@@ -33,6 +33,8 @@ pub struct FloatingText {
     text: String,
     #[serde(default = "default_scale")]
     scale: f32,
+    #[serde(default)]
+    hidden_at_start: bool,
 }
 
 fn default_scale() -> f32 {
@@ -65,6 +67,9 @@ fn populate(mut populate: YoleckPopulate<FloatingText>, game_assets: Res<GameAss
                 rotation: Default::default(),
                 scale: Vec3::new(data.scale, data.scale, 1.0),
             },
+            visibility: Visibility {
+                is_visible: ctx.is_in_editor() || !data.hidden_at_start,
+            },
             ..Default::default()
         });
         cmd.insert(CameraInclude);
@@ -75,5 +80,6 @@ fn edit(mut edit: YoleckEdit<FloatingText>) {
     edit.edit(|_ctx, data, ui| {
         ui.text_edit_multiline(&mut data.text);
         ui.add(egui::Slider::new(&mut data.scale, 0.005..=0.05).logarithmic(true));
+        ui.checkbox(&mut data.hidden_at_start, "Hidden At Start");
     });
 }
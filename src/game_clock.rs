@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use bevy_pkv::PkvStore;
+use bevy_rapier2d::plugin::{RapierConfiguration, TimestepMode};
+
+use crate::global_types::AppState;
+
+pub struct GameClockPlugin;
+
+impl Plugin for GameClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameClock>();
+        app.add_startup_system(load_game_speed);
+        app.add_system_to_stage(CoreStage::PreUpdate, advance_game_clock);
+        app.add_system(sync_physics_with_game_clock);
+        #[cfg(debug_assertions)]
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            control_frame_stepping.before(advance_game_clock),
+        );
+    }
+}
+
+/// The time gameplay systems should use instead of `Time`. It only advances during
+/// `AppState::Game`, and is affected by the game speed setting.
+pub struct GameClock {
+    delta: f32,
+    pub game_speed: f32,
+    /// Set by systems that take over the simulation, like rewinding.
+    pub frozen: bool,
+    /// Debug builds only - when set, the clock only advances when a step is requested.
+    frame_stepping: bool,
+    step_requested: bool,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self {
+            delta: 0.0,
+            game_speed: 1.0,
+            frozen: false,
+            frame_stepping: false,
+            step_requested: false,
+        }
+    }
+}
+
+impl GameClock {
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta
+    }
+
+    pub fn is_running(&self) -> bool {
+        0.0 < self.delta
+    }
+
+    pub fn save_game_speed(&self, pkv: &mut PkvStore) {
+        if let Err(err) = pkv.set(GAME_SPEED_PKV_KEY, &self.game_speed) {
+            error!("Cannot save game speed: {}", err);
+        }
+    }
+}
+
+pub const GAME_SPEEDS: [f32; 4] = [1.0, 0.75, 0.5, 0.25];
+
+const GAME_SPEED_PKV_KEY: &str = "game_speed";
+
+/// Used when stepping frame by frame, so that every step is the same no matter how long it took
+/// to press the key.
+const FRAME_STEP_DURATION: f32 = 1.0 / 60.0;
+
+fn load_game_speed(pkv: Res<PkvStore>, mut game_clock: ResMut<GameClock>) {
+    if let Ok(game_speed) = pkv.get::<f32>(GAME_SPEED_PKV_KEY) {
+        game_clock.game_speed = game_speed;
+    }
+}
+
+fn advance_game_clock(
+    time: Res<Time>,
+    state: Res<State<AppState>>,
+    mut game_clock: ResMut<GameClock>,
+) {
+    let is_in_game = *state.current() == AppState::Game;
+    let real_delta = if !is_in_game || game_clock.frozen {
+        0.0
+    } else if game_clock.frame_stepping {
+        if game_clock.step_requested {
+            FRAME_STEP_DURATION
+        } else {
+            0.0
+        }
+    } else {
+        time.delta_seconds()
+    };
+    game_clock.step_requested = false;
+    game_clock.delta = real_delta * game_clock.game_speed;
+}
+
+fn sync_physics_with_game_clock(
+    game_clock: Res<GameClock>,
+    mut rapier_configuration: ResMut<RapierConfiguration>,
+) {
+    rapier_configuration.physics_pipeline_active = game_clock.is_running();
+    if let TimestepMode::Variable { time_scale, .. } = &mut rapier_configuration.timestep_mode {
+        *time_scale = game_clock.game_speed;
+    }
+}
+
+/// F9 toggles frame stepping, and F10 advances a single frame while it is on.
+#[cfg(debug_assertions)]
+fn control_frame_stepping(keyboard: Res<Input<KeyCode>>, mut game_clock: ResMut<GameClock>) {
+    if keyboard.just_pressed(KeyCode::F9) {
+        game_clock.frame_stepping = !game_clock.frame_stepping;
+        info!("Frame stepping: {}", game_clock.frame_stepping);
+    }
+    if game_clock.frame_stepping && keyboard.just_pressed(KeyCode::F10) {
+        game_clock.step_requested = true;
+    }
+}
@@ -0,0 +1,205 @@
+use bevy::prelude::*;
+use bevy_pkv::PkvStore;
+use serde::{Deserialize, Serialize};
+
+use crate::door::handle_door_reached_events;
+use crate::game_clock::GameClock;
+use crate::global_types::{AppState, IsPlayer, LevelCompleted, LevelProgress, SpawnedDuringLevel};
+use crate::loading::GameAssets;
+use crate::utils::some_or;
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostRecording>();
+        app.add_startup_system(load_ghost_settings);
+        app.add_system_set(
+            SystemSet::on_enter(AppState::LoadLevel).with_system(prepare_ghost_for_level),
+        );
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game)
+                .with_system(record_player_samples)
+                .with_system(
+                    save_best_ghost
+                        .after(record_player_samples)
+                        .after(handle_door_reached_events),
+                )
+                .with_system(spawn_ghost_player)
+                .with_system(play_back_ghost.after(record_player_samples))
+        });
+    }
+}
+
+const GHOST_SETTINGS_PKV_KEY: &str = "ghost_settings";
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct GhostSettings {
+    pub enabled: bool,
+}
+
+impl Default for GhostSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+impl GhostSettings {
+    pub fn save(&self, pkv: &mut PkvStore) {
+        if let Err(err) = pkv.set(GHOST_SETTINGS_PKV_KEY, self) {
+            error!("Cannot save ghost settings: {}", err);
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct GhostSample {
+    time: f32,
+    position: Vec2,
+    rotation: Quat,
+}
+
+/// The samples of a single attempt, and the time it took to complete the level.
+#[derive(Default, Clone, Serialize, Deserialize)]
+struct GhostRun {
+    time: f32,
+    samples: Vec<GhostSample>,
+}
+
+#[derive(Default)]
+struct GhostRecording(GhostRun);
+
+/// Exists as a resource only when there is a ghost to show in the current level.
+struct BestGhost(GhostRun);
+
+#[derive(Component)]
+struct GhostPlayer;
+
+fn ghost_pkv_key(level: &str) -> String {
+    format!("ghost:{}", level)
+}
+
+fn load_ghost_settings(pkv: Res<PkvStore>, mut commands: Commands) {
+    commands.insert_resource(
+        pkv.get::<GhostSettings>(GHOST_SETTINGS_PKV_KEY)
+            .unwrap_or_default(),
+    );
+}
+
+fn prepare_ghost_for_level(
+    mut recording: ResMut<GhostRecording>,
+    level_progress: Res<LevelProgress>,
+    ghost_settings: Option<Res<GhostSettings>>,
+    pkv: Res<PkvStore>,
+    mut commands: Commands,
+) {
+    recording.0 = GhostRun::default();
+    commands.remove_resource::<BestGhost>();
+    if !ghost_settings.map_or(false, |ghost_settings| ghost_settings.enabled) {
+        return;
+    }
+    let level = some_or!(level_progress.current_level.as_ref(); return);
+    if let Ok(best_ghost) = pkv.get::<GhostRun>(&ghost_pkv_key(level)) {
+        commands.insert_resource(BestGhost(best_ghost));
+    }
+}
+
+fn record_player_samples(
+    game_clock: Res<GameClock>,
+    mut recording: ResMut<GhostRecording>,
+    player_query: Query<&GlobalTransform, With<IsPlayer>>,
+) {
+    recording.0.time += game_clock.delta_seconds();
+    let time = recording.0.time;
+    for player_transform in player_query.iter() {
+        recording.0.samples.push(GhostSample {
+            time,
+            position: player_transform.translation.truncate(),
+            rotation: player_transform.rotation,
+        });
+    }
+}
+
+fn save_best_ghost(
+    mut reader: EventReader<LevelCompleted>,
+    recording: Res<GhostRecording>,
+    level_progress: Res<LevelProgress>,
+    mut pkv: ResMut<PkvStore>,
+) {
+    if reader.iter().next().is_none() {
+        return;
+    }
+    let level = some_or!(
+        level_progress.current_level.as_ref();
+        return // level completed inside editor
+    );
+    let key = ghost_pkv_key(level);
+    if let Ok(best_ghost) = pkv.get::<GhostRun>(&key) {
+        if best_ghost.time <= recording.0.time {
+            return;
+        }
+    }
+    if let Err(err) = pkv.set(&key, &recording.0) {
+        error!("Cannot save ghost for {}: {}", level, err);
+    }
+}
+
+fn spawn_ghost_player(
+    best_ghost: Option<Res<BestGhost>>,
+    ghost_query: Query<(), With<GhostPlayer>>,
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+) {
+    if best_ghost.is_none() || !ghost_query.is_empty() {
+        return;
+    }
+    let mut cmd = commands.spawn();
+    cmd.insert_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgba(0.6, 0.8, 1.0, 0.35),
+            custom_size: Some(Vec2::new(1.0, 1.0)),
+            ..Default::default()
+        },
+        texture: game_assets.player.clone(),
+        // Hidden until the first sample is applied
+        visibility: Visibility { is_visible: false },
+        ..Default::default()
+    });
+    cmd.insert(GhostPlayer);
+    cmd.insert(SpawnedDuringLevel);
+}
+
+fn play_back_ghost(
+    best_ghost: Option<Res<BestGhost>>,
+    recording: Res<GhostRecording>,
+    mut ghost_query: Query<(&mut Transform, &mut Visibility), With<GhostPlayer>>,
+) {
+    let best_ghost = some_or!(best_ghost; return);
+    let samples = &best_ghost.0.samples;
+    let time = recording.0.time;
+    let next_index = samples.partition_point(|sample| sample.time < time);
+    let (position, rotation) = match (
+        next_index
+            .checked_sub(1)
+            .and_then(|index| samples.get(index)),
+        samples.get(next_index),
+    ) {
+        (Some(prev), Some(next)) => {
+            let factor = (time - prev.time) / (next.time - prev.time).max(f32::EPSILON);
+            (
+                prev.position.lerp(next.position, factor),
+                prev.rotation.slerp(next.rotation, factor),
+            )
+        }
+        (Some(sample), None) | (None, Some(sample)) => (sample.position, sample.rotation),
+        (None, None) => {
+            return;
+        }
+    };
+    for (mut transform, mut visibility) in ghost_query.iter_mut() {
+        // Slightly below the player so the real one is always drawn on top
+        transform.translation = position.extend(-0.05);
+        transform.rotation = rotation;
+        visibility.is_visible = true;
+    }
+}
@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 use ezinput::prelude::BindingTypeView;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, PartialEq)]
 pub struct MenuActionForKbgp;
@@ -17,7 +18,10 @@ pub enum AppState {
 #[derive(Clone, Hash, Debug, PartialEq, Eq)]
 pub enum MenuState {
     Main,
+    Settings,
+    Achievements,
     LevelSelect,
+    LevelSplash,
     Pause,
     LevelCompleted,
     GameOver,
@@ -32,6 +36,12 @@ pub struct LevelProgress {
     pub just_completed: Option<String>,
     pub current_level: Option<String>,
     pub num_levels_available: usize,
+    pub retries: usize,
+    /// Loading this level again counts as a retry. Cleared when a level is completed, so that
+    /// replaying a finished level doesn't count.
+    pub last_loaded_level: Option<String>,
+    /// Set by quick restart, so that the level is restarted without showing its splash screen.
+    pub skip_level_splash: bool,
 }
 
 #[derive(BindingTypeView, Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -40,19 +50,61 @@ pub enum InputBinding {
     MoveHorizontal,
     MoveVertical,
     Grab,
+    QuickRestart,
+    Rewind,
 }
 
 #[derive(Component)]
 pub struct CameraInclude;
 
+/// For entities that are not managed by Yoleck but should still be removed when the level is.
+#[derive(Component)]
+pub struct SpawnedDuringLevel;
+
 #[derive(Component)]
 pub struct IsPlayer;
 
 #[derive(Component)]
 pub struct IsZombie;
 
+/// How the player holds on to an entity.
+#[derive(Component, Clone, Copy)]
+pub struct Grabbable {
+    /// Stiffness of the motors that keep the entity in the grabber's hands.
+    pub stiffness: f32,
+    /// Where the entity is held, relative to the grabber's hands.
+    pub anchor: Vec2,
+    pub mode: GrabMode,
+    /// The grab fails if the entity is heavier than this.
+    pub max_mass: f32,
+}
+
+impl Default for Grabbable {
+    fn default() -> Self {
+        Self {
+            stiffness: 1000.0,
+            anchor: Vec2::new(0.0, 0.5),
+            mode: GrabMode::Drag,
+            max_mass: f32::INFINITY,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GrabMode {
+    /// Pulled along the floor, pushing whatever it bumps into.
+    Drag,
+    /// Carried over everything else, without colliding with it.
+    Lift,
+}
+
+/// Weakens WiFi signal that passes within `radius` of the entity.
 #[derive(Component)]
-pub struct Grabbable;
+pub struct SignalAttenuator {
+    pub radius: f32,
+    /// How much of the signal is left after passing through.
+    pub signal_left: f32,
+}
 
 #[derive(Component)]
 pub enum GrabStatus {
@@ -65,20 +117,35 @@ pub enum GrabStatus {
 #[derive(Component)]
 pub struct WifiRouter {
     pub full_strengh_radius: f32,
+    pub is_online: bool,
+    /// `None` for routers that transmit in all directions.
+    pub beam: Option<SignalBeam>,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SignalBeam {
+    /// Angle in radians, counter-clockwise from the X axis.
+    pub direction: f32,
+    /// Full angular width of the beam, in radians.
+    pub width: f32,
 }
 
 #[derive(Component)]
 pub struct DoorStatus {
     pub is_open: bool,
+    pub is_forced_open: bool,
 }
 
 #[derive(Default, Component)]
 pub struct WifiClient {
     pub access_point: Option<Entity>,
     pub signal_strength: f32,
+    /// No signal reaches the client at all, because of a jammer or a Faraday zone. Unlike just
+    /// having no `access_point`, this can happen even when there are online routers around.
+    pub is_jammed: bool,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub enum DownloadProgress {
     Disconnected,
     LosingConnection {
@@ -90,3 +157,63 @@ pub enum DownloadProgress {
     },
     Completed,
 }
+
+impl DownloadProgress {
+    pub fn progress(&self) -> f32 {
+        match self {
+            DownloadProgress::Disconnected => 0.0,
+            DownloadProgress::LosingConnection { progress, .. } => *progress,
+            DownloadProgress::Downloading { progress } => *progress,
+            DownloadProgress::Completed => 1.0,
+        }
+    }
+}
+
+pub struct DownloadStarted {
+    pub client: Entity,
+}
+
+/// The client's signal dropped mid-download and it started counting down to disconnection.
+pub struct ConnectionLost {
+    pub client: Entity,
+    pub progress: f32,
+}
+
+pub struct Reconnected {
+    pub client: Entity,
+    pub progress: f32,
+}
+
+pub struct DownloadCompleted {
+    pub client: Entity,
+}
+
+pub struct AccessPointChanged {
+    pub client: Entity,
+    pub previous: Option<Entity>,
+    pub current: Option<Entity>,
+}
+
+pub struct GrabStarted {
+    pub grabber: Entity,
+    pub grabbed: Entity,
+}
+
+pub struct GrabReleased {
+    pub grabber: Entity,
+    pub grabbed: Entity,
+}
+
+/// Sent instead of `GrabStarted` when the entity is heavier than its `Grabbable::max_mass`.
+pub struct GrabTooHeavy {
+    pub grabber: Entity,
+    pub grabbed: Entity,
+}
+
+pub struct DoorOpened {
+    pub door: Entity,
+}
+
+pub struct LevelCompleted {
+    pub door: Entity,
+}
@@ -0,0 +1,283 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_rapier2d::pipeline::CollisionEvent;
+use bevy_rapier2d::prelude::{ActiveEvents, Collider, Damping, Sensor};
+use bevy_rapier2d::rapier::prelude::CollisionEventFlags;
+use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
+use bevy_yoleck::{egui, YoleckEdit, YoleckExtForApp, YoleckPopulate, YoleckTypeHandler};
+use serde::{Deserialize, Serialize};
+
+use crate::global_types::{AppState, GameSystemLabel, GrabReleased, GrabStatus};
+use crate::movement_resolver::MoveController;
+use crate::utils::entities_ordered_by_type;
+
+pub struct HazardZonePlugin;
+
+impl Plugin for HazardZonePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<HazardZone>::new("HazardZone")
+                .populate_with(populate)
+                .with(yoleck_vpeol_position_edit_adapter(
+                    |hazard_zone: &mut HazardZone| YoleckVpeolTransform2dProjection {
+                        translation: &mut hazard_zone.position,
+                    },
+                ))
+                .edit_with(edit)
+        });
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game)
+                .with_system(track_bodies_in_hazard_zones)
+                .with_system(
+                    apply_hazard_effects
+                        .after(track_bodies_in_hazard_zones)
+                        .before(GameSystemLabel::ApplyMovement),
+                )
+                .with_system(fail_grabs_in_stun_zones.after(track_bodies_in_hazard_zones))
+        });
+        app.add_system_set(
+            SystemSet::on_enter(AppState::LoadLevel).with_system(clear_bodies_in_hazard_zones),
+        );
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct HazardZone {
+    #[serde(default)]
+    position: Vec2,
+    #[serde(default = "default_size")]
+    size: Vec2,
+    #[serde(default)]
+    kind: HazardKind,
+}
+
+fn default_size() -> Vec2 {
+    Vec2::new(2.0, 2.0)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum HazardKind {
+    /// Spilled coffee - hard to start moving and hard to stop.
+    Slippery,
+    /// Thick carpet.
+    Slow,
+    /// Tangled cables - nothing can be grabbed or held inside.
+    Stun,
+}
+
+impl Default for HazardKind {
+    fn default() -> Self {
+        Self::Slippery
+    }
+}
+
+impl HazardKind {
+    const ALL: [HazardKind; 3] = [HazardKind::Slippery, HazardKind::Slow, HazardKind::Stun];
+
+    fn color(&self) -> Color {
+        match self {
+            HazardKind::Slippery => Color::rgba(0.45, 0.3, 0.15, 0.5),
+            HazardKind::Slow => Color::rgba(0.6, 0.1, 0.15, 0.4),
+            HazardKind::Stun => Color::rgba(0.1, 0.1, 0.1, 0.5),
+        }
+    }
+
+    fn speed_factor(&self) -> f32 {
+        match self {
+            HazardKind::Slow => 0.4,
+            HazardKind::Slippery | HazardKind::Stun => 1.0,
+        }
+    }
+
+    fn traction(&self) -> f32 {
+        match self {
+            HazardKind::Slippery => 0.15,
+            HazardKind::Slow | HazardKind::Stun => 1.0,
+        }
+    }
+
+    fn damping_factor(&self) -> f32 {
+        match self {
+            HazardKind::Slippery => 0.1,
+            HazardKind::Slow | HazardKind::Stun => 1.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct HazardZoneStatus {
+    kind: HazardKind,
+    bodies_inside: HashSet<Entity>,
+}
+
+impl HazardZoneStatus {
+    pub fn stuns(&self, entity: Entity) -> bool {
+        self.kind == HazardKind::Stun && self.bodies_inside.contains(&entity)
+    }
+}
+
+/// The linear damping a body had before hazard zones started messing with it.
+#[derive(Component)]
+struct BaseLinearDamping(f32);
+
+fn populate(mut populate: YoleckPopulate<HazardZone>) {
+    populate.populate(|_ctx, data, mut cmd| {
+        cmd.insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: data.kind.color(),
+                custom_size: Some(data.size),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(data.position.extend(-0.3)),
+            ..Default::default()
+        });
+        cmd.insert(HazardZoneStatus {
+            kind: data.kind,
+            bodies_inside: Default::default(),
+        });
+        cmd.insert(Collider::cuboid(0.5 * data.size.x, 0.5 * data.size.y));
+        cmd.insert(Sensor(true));
+        cmd.insert(ActiveEvents::COLLISION_EVENTS);
+    });
+}
+
+fn edit(mut edit: YoleckEdit<HazardZone>) {
+    edit.edit(|_ctx, data, ui| {
+        egui::ComboBox::from_id_source("hazard-kind")
+            .selected_text(format!("Kind: {:?}", data.kind))
+            .show_ui(ui, |ui| {
+                for option in HazardKind::ALL {
+                    ui.selectable_value(&mut data.kind, option, format!("{:?}", option));
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut data.size.x)
+                    .prefix("Width:")
+                    .speed(0.05),
+            );
+            ui.add(
+                egui::DragValue::new(&mut data.size.y)
+                    .prefix("Height:")
+                    .speed(0.05),
+            );
+        });
+    });
+}
+
+fn track_bodies_in_hazard_zones(
+    mut reader: EventReader<CollisionEvent>,
+    bodies_query: Query<(), With<MoveController>>,
+    mut zones_query: Query<&mut HazardZoneStatus>,
+) {
+    // Bodies can be despawned while inside a zone, without a collision event to say so.
+    for mut zone_status in zones_query.iter_mut() {
+        zone_status
+            .bodies_inside
+            .retain(|body_entity| bodies_query.get(*body_entity).is_ok());
+    }
+    for event in reader.iter() {
+        let (entity1, entity2, flags, is_started) = match event {
+            CollisionEvent::Started(entity1, entity2, flags) => (entity1, entity2, flags, true),
+            CollisionEvent::Stopped(entity1, entity2, flags) => (entity1, entity2, flags, false),
+        };
+        if !flags.contains(CollisionEventFlags::SENSOR) {
+            continue;
+        }
+        let [body_entity, zone_entity] = if let Some(entities) =
+            entities_ordered_by_type!([*entity1, *entity2], bodies_query, zones_query)
+        {
+            entities
+        } else {
+            continue;
+        };
+        let mut zone_status = zones_query.get_mut(zone_entity).unwrap();
+        if is_started {
+            zone_status.bodies_inside.insert(body_entity);
+        } else {
+            zone_status.bodies_inside.remove(&body_entity);
+        }
+    }
+}
+
+fn clear_bodies_in_hazard_zones(mut zones_query: Query<&mut HazardZoneStatus>) {
+    for mut zone_status in zones_query.iter_mut() {
+        zone_status.bodies_inside.clear();
+    }
+}
+
+fn apply_hazard_effects(
+    zones_query: Query<&HazardZoneStatus>,
+    mut bodies_query: Query<(
+        Entity,
+        &mut MoveController,
+        &mut Damping,
+        Option<&BaseLinearDamping>,
+    )>,
+    mut commands: Commands,
+) {
+    for (body_entity, mut move_controller, mut damping, base_linear_damping) in
+        bodies_query.iter_mut()
+    {
+        let base_linear_damping = if let Some(base_linear_damping) = base_linear_damping {
+            base_linear_damping.0
+        } else {
+            commands
+                .entity(body_entity)
+                .insert(BaseLinearDamping(damping.linear_damping));
+            damping.linear_damping
+        };
+        let (mut speed_factor, mut traction, mut damping_factor) = (1.0, 1.0, 1.0);
+        for zone_status in zones_query.iter() {
+            if zone_status.bodies_inside.contains(&body_entity) {
+                speed_factor *= zone_status.kind.speed_factor();
+                traction *= zone_status.kind.traction();
+                damping_factor *= zone_status.kind.damping_factor();
+            }
+        }
+        move_controller.speed_factor = speed_factor;
+        move_controller.traction = traction;
+        damping.linear_damping = base_linear_damping * damping_factor;
+    }
+}
+
+/// A grab fails if either the grabber or whatever it tries to grab is in a stun zone.
+fn fail_grabs_in_stun_zones(
+    zones_query: Query<&HazardZoneStatus>,
+    mut grabbers_query: Query<(Entity, &mut GrabStatus)>,
+    mut commands: Commands,
+    mut grab_released_writer: EventWriter<GrabReleased>,
+) {
+    let stunned: HashSet<Entity> = zones_query
+        .iter()
+        .filter(|zone_status| zone_status.kind == HazardKind::Stun)
+        .flat_map(|zone_status| zone_status.bodies_inside.iter().copied())
+        .collect();
+    if stunned.is_empty() {
+        return;
+    }
+    for (grabber_entity, mut grab_status) in grabbers_query.iter_mut() {
+        match *grab_status {
+            GrabStatus::NoGrab | GrabStatus::GrabFailed => {}
+            GrabStatus::Reaching { hands_entity, .. } => {
+                if stunned.contains(&grabber_entity) {
+                    commands.entity(hands_entity).despawn_recursive();
+                    *grab_status = GrabStatus::GrabFailed;
+                }
+            }
+            GrabStatus::Holding {
+                hands_entity,
+                other,
+            } => {
+                if stunned.contains(&grabber_entity) || stunned.contains(&other) {
+                    commands.entity(hands_entity).despawn_recursive();
+                    grab_released_writer.send(GrabReleased {
+                        grabber: grabber_entity,
+                        grabbed: other,
+                    });
+                    *grab_status = GrabStatus::GrabFailed;
+                }
+            }
+        }
+    }
+}
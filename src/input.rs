@@ -80,6 +80,20 @@ impl Default for InputConfig {
             binding
         });
 
+        view.add_binding(&mut {
+            let mut binding = ActionBinding::from(InputBinding::QuickRestart);
+            binding.receivers(InputReceiver::KeyboardKey(KeyCode::R).into());
+            binding.receivers(InputReceiver::GamepadButton(GamepadButtonType::Select).into());
+            binding
+        });
+
+        view.add_binding(&mut {
+            let mut binding = ActionBinding::from(InputBinding::Rewind);
+            binding.receivers(InputReceiver::KeyboardKey(KeyCode::Q).into());
+            binding.receivers(InputReceiver::GamepadButton(GamepadButtonType::West).into());
+            binding
+        });
+
         Self(view)
     }
 }
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use bevy::sprite::MaterialMesh2dBundle;
+use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
+use bevy_yoleck::{egui, YoleckEdit, YoleckExtForApp, YoleckPopulate, YoleckTypeHandler};
+use serde::{Deserialize, Serialize};
+
+use crate::patrol_path::PatrolPath;
+use crate::wall::convex_polygon_mesh;
+
+pub struct JammerPlugin;
+
+impl Plugin for JammerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<Jammer>::new("Jammer")
+                .populate_with(populate_jammer)
+                .with(yoleck_vpeol_position_edit_adapter(|jammer: &mut Jammer| {
+                    YoleckVpeolTransform2dProjection {
+                        translation: &mut jammer.position,
+                    }
+                }))
+                .edit_with(edit_jammer)
+        });
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<FaradayZone>::new("FaradayZone")
+                .populate_with(populate_faraday_zone)
+                .with(yoleck_vpeol_position_edit_adapter(
+                    |faraday_zone: &mut FaradayZone| YoleckVpeolTransform2dProjection {
+                        translation: &mut faraday_zone.position,
+                    },
+                ))
+                .edit_with(edit_faraday_zone)
+        });
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Jammer {
+    #[serde(default)]
+    position: Vec2,
+    #[serde(default = "default_jammer_radius")]
+    radius: f32,
+    /// Subtracted from the signal strength. `1.0` or more blocks the signal completely.
+    #[serde(default = "default_jammer_strength")]
+    strength: f32,
+    #[serde(default)]
+    path: PatrolPath,
+}
+
+fn default_jammer_radius() -> f32 {
+    2.0
+}
+
+fn default_jammer_strength() -> f32 {
+    1.0
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct FaradayZone {
+    #[serde(default)]
+    position: Vec2,
+    #[serde(default = "default_faraday_zone_size")]
+    size: Vec2,
+}
+
+fn default_faraday_zone_size() -> Vec2 {
+    Vec2::new(2.0, 2.0)
+}
+
+#[derive(Component)]
+pub struct SignalJammer {
+    radius: f32,
+    strength: f32,
+}
+
+impl SignalJammer {
+    /// How much to subtract from the signal strength of a client at `client_position`.
+    pub fn jamming_at(&self, jammer_position: Vec2, client_position: Vec2) -> f32 {
+        if jammer_position.distance_squared(client_position) <= self.radius.powi(2) {
+            self.strength
+        } else {
+            0.0
+        }
+    }
+}
+
+/// No signal gets inside.
+#[derive(Component)]
+pub struct FaradayCage {
+    size: Vec2,
+}
+
+impl FaradayCage {
+    pub fn contains(&self, cage_position: Vec2, point: Vec2) -> bool {
+        let offset = (point - cage_position).abs();
+        offset.x <= 0.5 * self.size.x && offset.y <= 0.5 * self.size.y
+    }
+}
+
+const JAMMER_COLOR: Color = Color::rgb(0.8, 0.1, 0.6);
+
+fn populate_jammer(
+    mut populate: YoleckPopulate<Jammer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    populate.populate(|ctx, data, mut cmd| {
+        cmd.despawn_descendants();
+        cmd.insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: JAMMER_COLOR,
+                custom_size: Some(Vec2::new(0.5, 0.5)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(data.position.extend(0.5)),
+            ..Default::default()
+        });
+        cmd.insert(SignalJammer {
+            radius: data.radius,
+            strength: data.strength,
+        });
+        let num_segments = 32;
+        let outline: Vec<Vec2> = (0..num_segments)
+            .map(|i| {
+                let angle = std::f32::consts::TAU * i as f32 / num_segments as f32;
+                data.radius * Vec2::new(angle.cos(), angle.sin())
+            })
+            .collect();
+        let mesh = meshes.add(convex_polygon_mesh(&outline));
+        let mut radius_color = JAMMER_COLOR;
+        radius_color.set_a(0.1);
+        let material = materials.add(ColorMaterial::from(radius_color));
+        cmd.with_children(|commands| {
+            commands.spawn_bundle(MaterialMesh2dBundle {
+                mesh: mesh.into(),
+                material,
+                transform: Transform::from_xyz(0.0, 0.0, -0.8),
+                ..Default::default()
+            });
+        });
+        data.path
+            .populate(&mut cmd, data.position, ctx.is_in_editor());
+    });
+}
+
+fn edit_jammer(mut edit: YoleckEdit<Jammer>) {
+    edit.edit(|_ctx, data, ui| {
+        ui.add(egui::Slider::new(&mut data.radius, 0.5..=10.0).prefix("Radius: "));
+        ui.add(egui::Slider::new(&mut data.strength, 0.1..=1.0).prefix("Strength: "));
+        data.path.edit(ui, "jammer");
+    });
+}
+
+fn populate_faraday_zone(mut populate: YoleckPopulate<FaradayZone>) {
+    populate.populate(|_ctx, data, mut cmd| {
+        cmd.insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.4, 0.5, 0.6, 0.35),
+                custom_size: Some(data.size),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(data.position.extend(-0.3)),
+            ..Default::default()
+        });
+        cmd.insert(FaradayCage { size: data.size });
+    });
+}
+
+fn edit_faraday_zone(mut edit: YoleckEdit<FaradayZone>) {
+    edit.edit(|_ctx, data, ui| {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut data.size.x)
+                    .prefix("Width:")
+                    .speed(0.05),
+            );
+            ui.add(
+                egui::DragValue::new(&mut data.size.y)
+                    .prefix("Height:")
+                    .speed(0.05),
+            );
+        });
+    });
+}
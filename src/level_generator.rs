@@ -0,0 +1,359 @@
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+
+use bevy::math::Affine2;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+
+use crate::door::Door;
+use crate::playable_area::{PlayableArea, PlayableAreaShape};
+use crate::player::Player;
+use crate::wall::{Wall, WallShape};
+use crate::wifi::{signal_strength, Wifi, CONNECTION_THRESHOLD};
+use crate::zombie::{Zombie, ZombieKind};
+
+const MAX_ATTEMPTS: usize = 1000;
+const GRID_RESOLUTION: f32 = 0.25;
+const BODY_RADIUS: f32 = 0.4;
+const ZOMBIES_THE_PLAYER_CAN_HANDLE: usize = 6;
+const POSITION_ATTEMPTS: usize = 100;
+
+/// Higher difficulties crowd the playable area with so many walls and zombies that a solvable
+/// layout is rarely found.
+pub const MAX_GENERATOR_DIFFICULTY: u32 = 10;
+
+/// Generate a level in the same JSON format Yoleck saves, or `None` if no solvable layout was
+/// found.
+pub fn generate_level(seed: u64, difficulty: u32) -> Option<serde_json::Value> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..MAX_ATTEMPTS).find_map(|_| {
+        let layout = LevelLayout::random(&mut rng, difficulty);
+        if layout.is_solvable() {
+            Some(layout.to_yoleck_json())
+        } else {
+            None
+        }
+    })
+}
+
+fn is_blocked_by_walls(walls: &[Wall], point: Vec2, margin: f32) -> bool {
+    walls.iter().any(|wall| {
+        let local = Affine2::from_angle_translation(wall.rotation, wall.position)
+            .inverse()
+            .transform_point2(point);
+        let half_size = 0.5 * wall.size + Vec2::splat(margin);
+        local.x.abs() <= half_size.x && local.y.abs() <= half_size.y
+    })
+}
+
+/// Keep sampling until `is_open` accepts the position. Gives up after `POSITION_ATTEMPTS` and
+/// returns the last sample, leaving it to `LevelLayout::is_solvable` to reject the layout.
+fn pick_open_position(
+    rng: &mut StdRng,
+    mut sample: impl FnMut(&mut StdRng) -> Vec2,
+    is_open: impl Fn(Vec2) -> bool,
+) -> Vec2 {
+    let mut position = sample(rng);
+    for _ in 1..POSITION_ATTEMPTS {
+        if is_open(position) {
+            break;
+        }
+        position = sample(rng);
+    }
+    position
+}
+
+struct LevelLayout {
+    playable_area: PlayableArea,
+    player: Player,
+    door: Door,
+    wifis: Vec<Wifi>,
+    zombies: Vec<Zombie>,
+    walls: Vec<Wall>,
+}
+
+impl LevelLayout {
+    fn random(rng: &mut StdRng, difficulty: u32) -> Self {
+        let difficulty = difficulty as f32;
+        let playable_area = PlayableArea {
+            position: Vec2::ZERO,
+            size: Vec2::new(10.0 + difficulty, 8.0 + 0.5 * difficulty),
+            shape: PlayableAreaShape::Rectangle,
+        };
+        let half_size = 0.5 * playable_area.size - Vec2::splat(1.0);
+        let random_position = |rng: &mut StdRng| {
+            Vec2::new(
+                rng.gen_range(-half_size.x..=half_size.x),
+                rng.gen_range(-half_size.y..=half_size.y),
+            )
+        };
+
+        let num_walls = difficulty as usize + rng.gen_range(0..3);
+        let walls: Vec<Wall> = (0..num_walls)
+            .map(|_| {
+                let size = if rng.gen() {
+                    Vec2::new(rng.gen_range(1.0..=4.0), 0.5)
+                } else {
+                    Vec2::new(0.5, rng.gen_range(1.0..=4.0))
+                };
+                Wall {
+                    position: random_position(rng),
+                    size,
+                    rotation: 0.0,
+                    shape: WallShape::Rectangle,
+                }
+            })
+            .collect();
+
+        // Entities are placed after the walls so that they can avoid them - otherwise crowded
+        // layouts would almost always get rejected for having something stuck inside a wall.
+        let is_open = |position: Vec2| {
+            let offset = position - playable_area.position;
+            let half_size = 0.5 * playable_area.size - Vec2::splat(BODY_RADIUS);
+            offset.x.abs() <= half_size.x
+                && offset.y.abs() <= half_size.y
+                && !is_blocked_by_walls(&walls, position, BODY_RADIUS)
+        };
+        let random_open_position =
+            |rng: &mut StdRng| pick_open_position(rng, random_position, is_open);
+
+        let player = Player {
+            position: random_open_position(rng),
+            rotation: rng.gen_range(-PI..PI),
+        };
+        let door = Door {
+            position: random_open_position(rng),
+        };
+
+        let num_wifis = 1 + rng.gen_range(0..=1) + difficulty as usize / 4;
+        let wifis: Vec<Wifi> = (0..num_wifis)
+            .map(|_| Wifi {
+                position: random_open_position(rng),
+                full_strengh_radius: rng.gen_range(0.0..=1.0),
+                path: Default::default(),
+                beam: None,
+                is_portable: false,
+            })
+            .collect();
+
+        // Spread evenly so that no router gets more zombies than the player can handle - more
+        // difficulty means more routers, not just more zombies on each of them.
+        let num_zombies = 2 * num_wifis + difficulty as usize;
+        let zombies = (0..num_zombies)
+            .map(|i| {
+                let wifi_position = wifis[i % num_wifis].position;
+                let position = pick_open_position(
+                    rng,
+                    |rng| {
+                        let angle = rng.gen_range(-PI..PI);
+                        let distance = rng.gen_range(0.6..=1.5);
+                        wifi_position + distance * Vec2::new(angle.cos(), angle.sin())
+                    },
+                    is_open,
+                );
+                let to_wifi = wifi_position - position;
+                Zombie {
+                    position,
+                    rotation: to_wifi.y.atan2(to_wifi.x),
+                    kind: ZombieKind::Regular,
+                }
+            })
+            .collect();
+
+        Self {
+            playable_area,
+            player,
+            door,
+            wifis,
+            zombies,
+            walls,
+        }
+    }
+
+    fn is_blocked_by_wall(&self, point: Vec2, margin: f32) -> bool {
+        is_blocked_by_walls(&self.walls, point, margin)
+    }
+
+    /// Which router each client would connect to, according to the same model `WifiClient`s use.
+    fn access_point_for(&self, position: Vec2) -> Option<(usize, f32)> {
+        self.wifis
+            .iter()
+            .enumerate()
+            .map(|(index, wifi)| {
+                let distance_sq = position.distance_squared(wifi.position);
+                (
+                    index,
+                    signal_strength(distance_sq, wifi.full_strengh_radius),
+                )
+            })
+            .max_by_key(|(_, signal_strength)| float_ord::FloatOrd(*signal_strength))
+    }
+
+    fn is_solvable(&self) -> bool {
+        let entity_positions = [self.player.position, self.door.position]
+            .into_iter()
+            .chain(self.wifis.iter().map(|wifi| wifi.position))
+            .chain(self.zombies.iter().map(|zombie| zombie.position));
+        let half_size = 0.5 * self.playable_area.size - Vec2::splat(BODY_RADIUS);
+        for position in entity_positions {
+            let offset = position - self.playable_area.position;
+            if half_size.x < offset.x.abs() || half_size.y < offset.y.abs() {
+                return false;
+            }
+            if self.is_blocked_by_wall(position, BODY_RADIUS) {
+                return false;
+            }
+        }
+        if self.player.position.distance(self.door.position) < 2.0 {
+            return false;
+        }
+
+        let mut zombies_per_wifi = vec![0; self.wifis.len()];
+        for zombie in self.zombies.iter() {
+            if let Some((index, _)) = self.access_point_for(zombie.position) {
+                zombies_per_wifi[index] += 1;
+            }
+        }
+
+        let reachable = self.reachable_cells();
+        let is_reachable = |position: Vec2| reachable.contains(&self.cell_of(position));
+        if !is_reachable(self.door.position) {
+            return false;
+        }
+        reachable.iter().any(|&cell| {
+            let position = self.cell_center(cell);
+            if let Some((index, signal_strength)) = self.access_point_for(position) {
+                CONNECTION_THRESHOLD <= signal_strength
+                    && zombies_per_wifi[index] <= ZOMBIES_THE_PLAYER_CAN_HANDLE
+            } else {
+                false
+            }
+        })
+    }
+
+    fn grid_dimensions(&self) -> (i32, i32) {
+        let dimensions = self.playable_area.size / GRID_RESOLUTION;
+        (dimensions.x as i32, dimensions.y as i32)
+    }
+
+    fn cell_of(&self, position: Vec2) -> (i32, i32) {
+        let offset = position - self.playable_area.position + 0.5 * self.playable_area.size;
+        let cell = offset / GRID_RESOLUTION;
+        (cell.x as i32, cell.y as i32)
+    }
+
+    fn cell_center(&self, (x, y): (i32, i32)) -> Vec2 {
+        self.playable_area.position - 0.5 * self.playable_area.size
+            + GRID_RESOLUTION * Vec2::new(x as f32 + 0.5, y as f32 + 0.5)
+    }
+
+    fn reachable_cells(&self) -> HashSet<(i32, i32)> {
+        let (width, height) = self.grid_dimensions();
+        let is_open = |(x, y): (i32, i32)| {
+            (0..width).contains(&x)
+                && (0..height).contains(&y)
+                && !self.is_blocked_by_wall(self.cell_center((x, y)), BODY_RADIUS)
+        };
+        let mut reachable = HashSet::default();
+        let start = self.cell_of(self.player.position);
+        if !is_open(start) {
+            return reachable;
+        }
+        let mut queue = VecDeque::new();
+        reachable.insert(start);
+        queue.push_back(start);
+        while let Some((x, y)) = queue.pop_front() {
+            for neighbor in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                if is_open(neighbor) && reachable.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        reachable
+    }
+
+    fn to_yoleck_json(&self) -> serde_json::Value {
+        fn entry(type_name: &str, data: &impl Serialize) -> serde_json::Value {
+            serde_json::json!([
+                {"type": type_name, "name": ""},
+                serde_json::to_value(data).expect("level data must be serializable"),
+            ])
+        }
+        let mut entries = vec![
+            entry("PlayableArea", &self.playable_area),
+            entry("Player", &self.player),
+            entry("Door", &self.door),
+        ];
+        entries.extend(self.wifis.iter().map(|wifi| entry("Wifi", wifi)));
+        entries.extend(self.zombies.iter().map(|zombie| entry("Zombie", zombie)));
+        entries.extend(self.walls.iter().map(|wall| entry("Wall", wall)));
+        serde_json::json!([{"format_version": 1}, {}, entries])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries_of_type<'a>(
+        level: &'a serde_json::Value,
+        type_name: &'a str,
+    ) -> impl Iterator<Item = &'a serde_json::Value> {
+        level[2]
+            .as_array()
+            .expect("entries must be an array")
+            .iter()
+            .filter(move |entry| entry[0]["type"] == type_name)
+            .map(|entry| &entry[1])
+    }
+
+    fn assert_round_trips<T: Serialize + serde::de::DeserializeOwned>(data: &serde_json::Value) {
+        let parsed: T = serde_json::from_value(data.clone()).unwrap();
+        assert_eq!(&serde_json::to_value(parsed).unwrap(), data);
+    }
+
+    #[test]
+    fn same_seed_and_difficulty_generate_same_level() {
+        for difficulty in [0, 3, MAX_GENERATOR_DIFFICULTY] {
+            assert_eq!(
+                generate_level(42, difficulty),
+                generate_level(42, difficulty)
+            );
+        }
+    }
+
+    #[test]
+    fn every_supported_difficulty_generates_a_level() {
+        for difficulty in 0..=MAX_GENERATOR_DIFFICULTY {
+            for seed in 0..8 {
+                assert!(
+                    generate_level(seed, difficulty).is_some(),
+                    "seed {} difficulty {}",
+                    seed,
+                    difficulty
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn generated_level_round_trips_through_level_data() {
+        let level = generate_level(7, 5).unwrap();
+        assert_eq!(entries_of_type(&level, "Player").count(), 1);
+        assert_eq!(entries_of_type(&level, "Door").count(), 1);
+        assert!(entries_of_type(&level, "Wifi").count() > 0);
+        assert!(entries_of_type(&level, "Zombie").count() > 0);
+        for data in entries_of_type(&level, "Wifi") {
+            assert_round_trips::<Wifi>(data);
+        }
+        for data in entries_of_type(&level, "Zombie") {
+            assert_round_trips::<Zombie>(data);
+        }
+        for data in entries_of_type(&level, "Wall") {
+            assert_round_trips::<Wall>(data);
+        }
+    }
+}
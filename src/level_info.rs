@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
+use bevy_yoleck::{
+    egui, YoleckEdit, YoleckExtForApp, YoleckLevelIndex, YoleckPopulate, YoleckRawLevel,
+    YoleckTypeHandler,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::loading::GameAssets;
+use crate::utils::some_or;
+
+pub struct LevelInfoPlugin;
+
+impl Plugin for LevelInfoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<LevelInfo>::new("LevelInfo")
+                .populate_with(populate)
+                .with(yoleck_vpeol_position_edit_adapter(
+                    |level_info: &mut LevelInfo| YoleckVpeolTransform2dProjection {
+                        translation: &mut level_info.thumbnail_position,
+                    },
+                ))
+                .edit_with(edit)
+        });
+        app.init_resource::<LevelInfoCache>();
+        app.add_system(collect_level_infos);
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelInfo {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub subtitle: String,
+    #[serde(default)]
+    pub par_time: Option<f32>,
+    #[serde(default)]
+    pub hint: String,
+    #[serde(default = "default_hint_after_retries")]
+    pub hint_after_retries: usize,
+    #[serde(default = "default_rewind_budget")]
+    pub rewind_budget: f32,
+    #[serde(default)]
+    pub zombies_perceive_player: bool,
+    #[serde(default)]
+    thumbnail_position: Vec2,
+    #[serde(default)]
+    thumbnail_size: Vec2,
+}
+
+fn default_hint_after_retries() -> usize {
+    3
+}
+
+pub fn default_rewind_budget() -> f32 {
+    5.0
+}
+
+impl LevelInfo {
+    pub fn hint_for_retries(&self, retries: usize) -> Option<&str> {
+        if self.hint.trim().is_empty() || retries < self.hint_after_retries {
+            None
+        } else {
+            Some(&self.hint)
+        }
+    }
+}
+
+/// How many seconds of rewinding the player may use in this level.
+#[derive(Component)]
+pub struct LevelRewindBudget(pub f32);
+
+/// Marks the level info of levels where zombies can see and react to the player.
+#[derive(Component)]
+pub struct ZombiesPerceivePlayer;
+
+/// The part of the level shown by the camera on the pre-level splash.
+#[derive(Component)]
+pub struct LevelThumbnail {
+    pub size: Vec2,
+}
+
+fn populate(mut populate: YoleckPopulate<LevelInfo>) {
+    populate.populate(|ctx, data, mut cmd| {
+        cmd.insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: if ctx.is_in_editor() {
+                    Color::rgba(0.2, 0.6, 1.0, 0.2)
+                } else {
+                    Color::NONE
+                },
+                custom_size: Some(data.thumbnail_size.max(Vec2::splat(0.5))),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(data.thumbnail_position.extend(-1.0)),
+            ..Default::default()
+        });
+        cmd.insert(LevelThumbnail {
+            size: data.thumbnail_size,
+        });
+        cmd.insert(LevelRewindBudget(data.rewind_budget));
+        if data.zombies_perceive_player {
+            cmd.insert(ZombiesPerceivePlayer);
+        } else {
+            cmd.remove::<ZombiesPerceivePlayer>();
+        }
+    });
+}
+
+fn edit(mut edit: YoleckEdit<LevelInfo>) {
+    edit.edit(|_ctx, data, ui| {
+        ui.horizontal(|ui| {
+            ui.label("Title:");
+            ui.text_edit_singleline(&mut data.title);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Subtitle:");
+            ui.text_edit_singleline(&mut data.subtitle);
+        });
+        ui.horizontal(|ui| {
+            let mut has_par_time = data.par_time.is_some();
+            ui.checkbox(&mut has_par_time, "Par Time");
+            if !has_par_time {
+                data.par_time = None;
+            } else if let Some(par_time) = data.par_time.as_mut() {
+                ui.add(
+                    egui::DragValue::new(par_time)
+                        .suffix("s")
+                        .clamp_range(0.0..=600.0),
+                );
+            } else {
+                data.par_time = Some(30.0);
+            }
+        });
+        ui.label("Hint:");
+        ui.text_edit_multiline(&mut data.hint);
+        ui.add(
+            egui::Slider::new(&mut data.hint_after_retries, 0..=10).prefix("Reveal Hint After: "),
+        );
+        ui.add(
+            egui::Slider::new(&mut data.rewind_budget, 0.0..=30.0)
+                .prefix("Rewind Budget: ")
+                .suffix("s"),
+        );
+        ui.checkbox(
+            &mut data.zombies_perceive_player,
+            "Zombies See And React To The Player",
+        );
+        ui.label("Thumbnail (drag to move):");
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut data.thumbnail_size.x)
+                    .prefix("Width:")
+                    .speed(0.05),
+            );
+            ui.add(
+                egui::DragValue::new(&mut data.thumbnail_size.y)
+                    .prefix("Height:")
+                    .speed(0.05),
+            );
+        });
+    });
+}
+
+/// Level metadata read directly from the level files, so that menus can show it without loading
+/// the levels.
+#[derive(Default)]
+pub struct LevelInfoCache {
+    handles: HashMap<String, Handle<YoleckRawLevel>>,
+    infos: HashMap<String, Option<LevelInfo>>,
+}
+
+impl LevelInfoCache {
+    pub fn get(&self, filename: &str) -> Option<&LevelInfo> {
+        self.infos.get(filename)?.as_ref()
+    }
+
+    pub fn display_title(&self, filename: &str) -> String {
+        if let Some(level_info) = self.get(filename) {
+            if !level_info.title.trim().is_empty() {
+                return level_info.title.clone();
+            }
+        }
+        format_level_name(filename)
+    }
+}
+
+fn format_level_name(filename: &str) -> String {
+    filename
+        .strip_suffix(".yol")
+        .unwrap_or(filename)
+        .replace('_', " ")
+}
+
+fn collect_level_infos(
+    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
+    level_index_assets: Res<Assets<YoleckLevelIndex>>,
+    raw_levels: Res<Assets<YoleckRawLevel>>,
+    mut cache: ResMut<LevelInfoCache>,
+) {
+    let level_index = some_or!(level_index_assets.get(&game_assets.level_index); return);
+    let cache = &mut *cache;
+    for level in level_index.iter() {
+        if cache.infos.contains_key(&level.filename) {
+            continue;
+        }
+        let handle = cache
+            .handles
+            .entry(level.filename.clone())
+            .or_insert_with(|| asset_server.load(&format!("levels/{}", level.filename)));
+        let raw_level = some_or!(raw_levels.get(&*handle); continue);
+        let level_info = raw_level
+            .entries()
+            .iter()
+            .find(|entry| entry.header.type_name == "LevelInfo")
+            .and_then(|entry| match serde_json::from_value(entry.data.clone()) {
+                Ok(level_info) => Some(level_info),
+                Err(err) => {
+                    error!("Bad LevelInfo in {:?}: {}", level.filename, err);
+                    None
+                }
+            });
+        cache.infos.insert(level.filename.clone(), level_info);
+        cache.handles.remove(&level.filename);
+    }
+}
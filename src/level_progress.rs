@@ -14,6 +14,9 @@ impl Plugin for LevelProgressPlugin {
             just_completed: None,
             current_level: None,
             num_levels_available: 0,
+            retries: 0,
+            last_loaded_level: None,
+            skip_level_splash: false,
         });
         app.add_system(read_last_finished_level);
         app.add_system_set(
@@ -97,6 +100,8 @@ fn handle_level_completion(
         .find(|level| level.filename == *completed_level)
         .expect("Current level must be in the index");
     level_progress.just_completed = level_progress.current_level.take();
+    level_progress.last_loaded_level = None;
+    level_progress.retries = 0;
     level_progress.current_level = it.next().map(|level| level.filename.clone());
     level_progress.num_levels_available = 0;
     state
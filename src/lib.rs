@@ -1,44 +1,87 @@
+mod achievements;
+mod antenna;
+#[cfg(feature = "audio")]
+mod audio;
 mod camera;
+mod checkpoint;
+mod conveyor;
 mod door;
 mod floating_text;
+mod game_clock;
+mod ghost;
 mod global_types;
+mod hazard_zone;
 mod input;
+mod jammer;
+mod level_generator;
+mod level_info;
 mod level_progress;
 mod loading;
 mod menu;
 mod movement_resolver;
+mod patrol_path;
 mod playable_area;
 mod player;
 mod player_control;
+mod prop;
+mod rewind;
 mod score;
+mod survival;
+mod tilemap;
+mod time_trial;
+mod trigger_zone;
 mod utils;
 mod wall;
 mod wifi;
 mod zombie;
+mod zombie_ai;
+mod zombie_perception;
+mod zombie_spawner;
 
 pub use crate::global_types::MenuActionForKbgp;
+pub use crate::level_generator::{generate_level, MAX_GENERATOR_DIFFICULTY};
 use crate::loading::LoadingPlugin;
 
 use bevy::app::App;
 use bevy::prelude::*;
-use bevy_rapier2d::plugin::RapierConfiguration;
 use bevy_yoleck::{YoleckLoadingCommand, YoleckManaged, YoleckSyncWithEditorState};
 
+use self::achievements::AchievementsPlugin;
+use self::antenna::AntennaPlugin;
+#[cfg(feature = "audio")]
+use self::audio::GameAudioPlugin;
 use self::camera::CameraPlugin;
+use self::checkpoint::CheckpointPlugin;
+use self::conveyor::ConveyorPlugin;
 use self::door::DoorPlugin;
 use self::floating_text::FloatingTextPlugin;
-use self::global_types::{AppState, LevelProgress, MenuState};
+use self::game_clock::GameClockPlugin;
+use self::ghost::GhostPlugin;
+use self::global_types::{AppState, LevelProgress, MenuState, SpawnedDuringLevel};
+use self::hazard_zone::HazardZonePlugin;
 use self::input::GameInputPlugin;
+use self::jammer::JammerPlugin;
+use self::level_info::LevelInfoPlugin;
 use self::level_progress::LevelProgressPlugin;
 use self::menu::MenuPlugin;
 use self::movement_resolver::MovementResolverPlugin;
+use self::patrol_path::PatrolPathPlugin;
 use self::playable_area::PlayableAreaPlugin;
 use self::player::PlayerPlugin;
 use self::player_control::PlayerControlPlugin;
+use self::prop::PropPlugin;
+use self::rewind::RewindPlugin;
 use self::score::ScorePlugin;
+use self::survival::SurvivalPlugin;
+use self::tilemap::TilemapPlugin;
+use self::time_trial::TimeTrialPlugin;
+use self::trigger_zone::TriggerZonePlugin;
 use self::wall::WallPlugin;
 use self::wifi::WifiPlugin;
 use self::zombie::ZombiePlugin;
+use self::zombie_ai::ZombieAiPlugin;
+use self::zombie_perception::ZombiePerceptionPlugin;
+use self::zombie_spawner::ZombieSpawnerPlugin;
 
 pub struct GamePlugin {
     pub is_editor: bool,
@@ -53,25 +96,48 @@ impl Plugin for GamePlugin {
         });
 
         app.add_plugin(GameInputPlugin);
+        app.add_plugin(GameClockPlugin);
         app.add_plugin(PlayerControlPlugin);
         app.add_plugin(MovementResolverPlugin);
         app.add_plugin(ScorePlugin);
+        #[cfg(feature = "audio")]
+        app.add_plugin(GameAudioPlugin);
 
         app.add_plugin(PlayerPlugin);
         app.add_plugin(WifiPlugin);
+        app.add_plugin(PatrolPathPlugin);
+        app.add_plugin(AntennaPlugin);
+        app.add_plugin(JammerPlugin);
         app.add_plugin(ZombiePlugin);
+        app.add_plugin(ZombiePerceptionPlugin);
+        app.add_plugin(ZombieAiPlugin);
+        app.add_plugin(ZombieSpawnerPlugin);
         app.add_plugin(DoorPlugin);
         app.add_plugin(PlayableAreaPlugin);
         app.add_plugin(WallPlugin);
+        app.add_plugin(TilemapPlugin);
+        app.add_plugin(PropPlugin);
+        app.add_plugin(HazardZonePlugin);
+        app.add_plugin(ConveyorPlugin);
         app.add_plugin(FloatingTextPlugin);
+        app.add_plugin(TriggerZonePlugin);
+        app.add_plugin(CheckpointPlugin);
+        app.add_plugin(RewindPlugin);
 
+        app.add_plugin(LevelInfoPlugin);
         app.add_plugin(LevelProgressPlugin);
-        app.add_system(enable_disable_physics);
+        app.add_plugin(SurvivalPlugin);
+        app.add_plugin(AchievementsPlugin);
+        app.add_plugin(GhostPlugin);
+        app.add_plugin(TimeTrialPlugin);
         if self.is_editor {
             app.add_plugin(YoleckSyncWithEditorState {
                 when_editor: AppState::Editor,
                 when_game: AppState::Game,
             });
+            app.add_system_set(
+                SystemSet::on_enter(AppState::Editor).with_system(despawn_spawned_entities),
+            );
         } else {
             app.add_plugin(MenuPlugin);
             app.add_state(AppState::Menu(MenuState::Main));
@@ -92,18 +158,20 @@ impl Plugin for GamePlugin {
     }
 }
 
-fn enable_disable_physics(
-    state: Res<State<AppState>>,
-    mut rapier_configuration: ResMut<RapierConfiguration>,
+fn despawn_spawned_entities(
+    query: Query<Entity, With<SpawnedDuringLevel>>,
+    mut commands: Commands,
 ) {
-    rapier_configuration.physics_pipeline_active = *state.current() == AppState::Game;
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
 }
 
 fn handle_level_loading(
-    level_entities_query: Query<Entity, With<YoleckManaged>>,
+    level_entities_query: Query<Entity, Or<(With<YoleckManaged>, With<SpawnedDuringLevel>)>>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    level_progress: Res<LevelProgress>,
+    mut level_progress: ResMut<LevelProgress>,
     mut yoleck_loading_command: ResMut<YoleckLoadingCommand>,
     mut state: ResMut<State<AppState>>,
 ) {
@@ -112,9 +180,20 @@ fn handle_level_loading(
     }
     let current_level = level_progress
         .current_level
-        .as_ref()
+        .clone()
         .expect("Entered LoadLevel state when current_level is None");
+    if level_progress.last_loaded_level.as_ref() == Some(&current_level) {
+        level_progress.retries += 1;
+    } else {
+        level_progress.retries = 0;
+    }
     *yoleck_loading_command =
         YoleckLoadingCommand::FromAsset(asset_server.load(&format!("levels/{}", current_level)));
-    state.set(AppState::Game).unwrap();
+    level_progress.last_loaded_level = Some(current_level);
+    if level_progress.skip_level_splash {
+        level_progress.skip_level_splash = false;
+        state.set(AppState::Game).unwrap();
+    } else {
+        state.set(AppState::Menu(MenuState::LevelSplash)).unwrap();
+    }
 }
@@ -23,6 +23,9 @@ pub struct GameAssets {
     #[asset(texture_atlas(tile_size_x = 64.0, tile_size_y = 64.0, columns = 2, rows = 1))]
     #[asset(path = "sprites/door.png")]
     pub door: Handle<TextureAtlas>,
+    #[asset(texture_atlas(tile_size_x = 64.0, tile_size_y = 64.0, columns = 3, rows = 1))]
+    #[asset(path = "sprites/tiles.png")]
+    pub tiles: Handle<TextureAtlas>,
     #[asset(path = "fonts/FiraSans-Bold.ttf")]
     pub font: Handle<Font>,
     #[asset(path = "levels/index.yoli")]
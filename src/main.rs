@@ -12,6 +12,7 @@ use bevy_rapier2d::plugin::RapierPhysicsPlugin;
 use clap::Parser;
 use signal_scuffle::GamePlugin;
 use signal_scuffle::MenuActionForKbgp;
+use signal_scuffle::{generate_level, MAX_GENERATOR_DIFFICULTY};
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -19,11 +20,38 @@ struct Args {
     editor: bool,
     #[clap(long)]
     level: Option<String>,
+    /// Generate a level into the given file (e.g. assets/levels/Generated.yol) instead of running
+    /// the game
+    #[clap(long)]
+    generate: Option<std::path::PathBuf>,
+    #[clap(long, default_value = "0")]
+    seed: u64,
+    #[clap(long, default_value = "1")]
+    difficulty: u32,
 }
 
 fn main() {
     let args = Args::parse();
 
+    if let Some(path) = &args.generate {
+        if MAX_GENERATOR_DIFFICULTY < args.difficulty {
+            eprintln!(
+                "Difficulty must be at most {}, got {}",
+                MAX_GENERATOR_DIFFICULTY, args.difficulty
+            );
+            std::process::exit(1);
+        }
+        let level = generate_level(args.seed, args.difficulty).unwrap_or_else(|| {
+            eprintln!(
+                "Unable to generate a solvable level with seed {} and difficulty {}",
+                args.seed, args.difficulty
+            );
+            std::process::exit(1);
+        });
+        std::fs::write(path, level.to_string()).expect("Unable to write the generated level");
+        return;
+    }
+
     let mut app = App::new();
     app.insert_resource(Msaa { samples: 1 });
     app.insert_resource(ClearColor(Color::rgb(0.4, 0.4, 0.4)));
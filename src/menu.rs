@@ -2,11 +2,21 @@ use bevy::prelude::*;
 use bevy_egui_kbgp::bevy_egui::EguiContext;
 use bevy_egui_kbgp::egui;
 use bevy_egui_kbgp::prelude::*;
+use bevy_pkv::PkvStore;
 use bevy_yoleck::YoleckLevelIndex;
 
+use crate::achievements::{UnlockedAchievements, ACHIEVEMENTS};
+#[cfg(feature = "audio")]
+use crate::audio::AudioSettings;
+use crate::checkpoint::{CheckpointSnapshot, RestoreCheckpoint};
+use crate::game_clock::{GameClock, GAME_SPEEDS};
+use crate::ghost::GhostSettings;
 use crate::global_types::LevelProgress;
 use crate::global_types::{AppState, MenuState};
+use crate::level_info::LevelInfoCache;
 use crate::loading::GameAssets;
+use crate::survival::{survival_high_score, SurvivalRun, SURVIVAL_LEVEL};
+use crate::time_trial::TimeTrialRun;
 use crate::utils::some_or;
 use crate::MenuActionForKbgp;
 
@@ -18,10 +28,21 @@ impl Plugin for MenuPlugin {
         app.add_system_set(
             SystemSet::on_update(AppState::Menu(MenuState::Main)).with_system(main_menu),
         );
+        app.add_system_set(
+            SystemSet::on_update(AppState::Menu(MenuState::Settings)).with_system(settings_menu),
+        );
+        app.add_system_set(
+            SystemSet::on_update(AppState::Menu(MenuState::Achievements))
+                .with_system(achievements_menu),
+        );
         app.add_system_set(
             SystemSet::on_update(AppState::Menu(MenuState::LevelSelect))
                 .with_system(level_select_menu),
         );
+        app.add_system_set(
+            SystemSet::on_update(AppState::Menu(MenuState::LevelSplash))
+                .with_system(level_splash_menu),
+        );
         app.add_system_set(
             SystemSet::on_update(AppState::Menu(MenuState::Pause)).with_system(pause_menu),
         );
@@ -55,6 +76,8 @@ fn pause_unpause_game(mut egui_context: ResMut<EguiContext>, mut state: ResMut<S
 #[derive(PartialEq)]
 enum FocusLabel {
     Start,
+    Settings,
+    Achievements,
     NextLevel,
     CurrentLevel,
     BackToMainMenu,
@@ -73,16 +96,12 @@ fn menu_layout(egui_context: &egui::Context, dlg: impl FnOnce(&mut egui::Ui)) {
         });
 }
 
-fn format_level_name(filename: &str) -> String {
-    filename
-        .strip_suffix(".yol")
-        .unwrap_or(filename)
-        .replace('_', " ")
-}
-
 fn main_menu(
     mut egui_context: ResMut<EguiContext>,
     mut state: ResMut<State<AppState>>,
+    mut level_progress: ResMut<LevelProgress>,
+    pkv: Res<PkvStore>,
+    mut commands: Commands,
     #[cfg(not(target_arch = "wasm32"))] mut exit: EventWriter<bevy::app::AppExit>,
 ) {
     menu_layout(egui_context.ctx_mut(), |ui| {
@@ -100,6 +119,38 @@ fn main_menu(
             ui.kbgp_clear_input();
             ui.kbgp_set_focus_label(FocusLabel::NextLevel);
         }
+        let high_score = survival_high_score(&pkv);
+        if ui
+            .button(if 0 < high_score {
+                format!("Survival (Best: {})", high_score)
+            } else {
+                "Survival".to_owned()
+            })
+            .kbgp_navigation()
+            .clicked()
+        {
+            commands.insert_resource(SurvivalRun::default());
+            level_progress.current_level = Some(SURVIVAL_LEVEL.to_owned());
+            state.set(AppState::LoadLevel).unwrap();
+        }
+        if ui
+            .button("Achievements")
+            .kbgp_navigation()
+            .kbgp_focus_label(FocusLabel::Achievements)
+            .clicked()
+        {
+            state.set(AppState::Menu(MenuState::Achievements)).unwrap();
+            ui.kbgp_clear_input();
+        }
+        if ui
+            .button("Settings")
+            .kbgp_navigation()
+            .kbgp_focus_label(FocusLabel::Settings)
+            .clicked()
+        {
+            state.set(AppState::Menu(MenuState::Settings)).unwrap();
+            ui.kbgp_clear_input();
+        }
         #[cfg(not(target_arch = "wasm32"))]
         if ui
             .button("Exit")
@@ -112,17 +163,157 @@ fn main_menu(
     });
 }
 
+#[cfg(feature = "audio")]
+fn volume_setting(ui: &mut egui::Ui, label: &str, volume: &mut f32) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        if ui.button("-").kbgp_navigation().clicked() {
+            *volume = (*volume - 0.1).max(0.0);
+            changed = true;
+        }
+        ui.label(
+            egui::RichText::new(format!("{}: {:.0}%", label, 100.0 * *volume))
+                .color(egui::Color32::WHITE)
+                .background_color(egui::Color32::BLACK),
+        );
+        if ui.button("+").kbgp_navigation().clicked() {
+            *volume = (*volume + 0.1).min(1.0);
+            changed = true;
+        }
+    });
+    changed
+}
+
+fn settings_menu(
+    mut egui_context: ResMut<EguiContext>,
+    mut state: ResMut<State<AppState>>,
+    #[cfg(feature = "audio")] mut audio_settings: Option<ResMut<AudioSettings>>,
+    mut ghost_settings: Option<ResMut<GhostSettings>>,
+    mut game_clock: ResMut<GameClock>,
+    mut pkv: ResMut<PkvStore>,
+) {
+    menu_layout(egui_context.ctx_mut(), |ui| {
+        #[cfg(feature = "audio")]
+        if let Some(audio_settings) = audio_settings.as_mut() {
+            let audio_settings = &mut **audio_settings;
+            let mut changed = false;
+            changed |= volume_setting(ui, "Master Volume", &mut audio_settings.master_volume);
+            changed |= volume_setting(ui, "Music Volume", &mut audio_settings.music_volume);
+            changed |= volume_setting(ui, "Effects Volume", &mut audio_settings.effects_volume);
+            if changed {
+                audio_settings.save(&mut pkv);
+            }
+        }
+        if let Some(ghost_settings) = ghost_settings.as_mut() {
+            if ui
+                .button(if ghost_settings.enabled {
+                    "Ghost Of Best Run: On"
+                } else {
+                    "Ghost Of Best Run: Off"
+                })
+                .kbgp_navigation()
+                .clicked()
+            {
+                ghost_settings.enabled = !ghost_settings.enabled;
+                ghost_settings.save(&mut pkv);
+            }
+        }
+        if ui
+            .button(format!("Game Speed: {:.0}%", 100.0 * game_clock.game_speed))
+            .kbgp_navigation()
+            .clicked()
+        {
+            // Cycle through the speeds, starting from full speed if the saved one is not listed
+            let next_index = GAME_SPEEDS
+                .iter()
+                .position(|&speed| speed == game_clock.game_speed)
+                .map_or(0, |index| (index + 1) % GAME_SPEEDS.len());
+            game_clock.game_speed = GAME_SPEEDS[next_index];
+            game_clock.save_game_speed(&mut pkv);
+        }
+        if ui
+            .button("Back To Menu")
+            .kbgp_navigation()
+            .kbgp_initial_focus()
+            .clicked()
+            || ui.kbgp_user_action() == Some(MenuActionForKbgp)
+        {
+            state.set(AppState::Menu(MenuState::Main)).unwrap();
+            ui.kbgp_clear_input();
+            ui.kbgp_set_focus_label(FocusLabel::Settings);
+        }
+    });
+}
+
+fn achievements_menu(
+    mut egui_context: ResMut<EguiContext>,
+    mut state: ResMut<State<AppState>>,
+    unlocked: Option<Res<UnlockedAchievements>>,
+) {
+    menu_layout(egui_context.ctx_mut(), |ui| {
+        if ui
+            .button("Back To Menu")
+            .kbgp_navigation()
+            .kbgp_initial_focus()
+            .clicked()
+            || ui.kbgp_user_action() == Some(MenuActionForKbgp)
+        {
+            state.set(AppState::Menu(MenuState::Main)).unwrap();
+            ui.kbgp_clear_input();
+            ui.kbgp_set_focus_label(FocusLabel::Achievements);
+        }
+        ui.add_space(8.0);
+        for achievement in ACHIEVEMENTS {
+            let is_unlocked = unlocked
+                .as_ref()
+                .map_or(false, |unlocked| unlocked.0.contains(achievement.key));
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} {}\n{}",
+                    if is_unlocked { "[X]" } else { "[ ]" },
+                    achievement.title,
+                    achievement.description,
+                ))
+                .color(if is_unlocked {
+                    egui::Color32::WHITE
+                } else {
+                    egui::Color32::GRAY
+                })
+                .background_color(egui::Color32::BLACK),
+            );
+        }
+    });
+}
+
 fn level_select_menu(
     mut egui_context: ResMut<EguiContext>,
     mut state: ResMut<State<AppState>>,
     mut level_progress: ResMut<LevelProgress>,
     game_assets: Res<GameAssets>,
     level_index_assets: Res<Assets<YoleckLevelIndex>>,
+    level_info_cache: Res<LevelInfoCache>,
+    time_trial_run: Option<Res<TimeTrialRun>>,
+    mut commands: Commands,
 ) {
     menu_layout(egui_context.ctx_mut(), |ui| {
         if ui.kbgp_user_action() == Some(MenuActionForKbgp) {
             ui.kbgp_set_focus_label(FocusLabel::BackToMainMenu);
         }
+        if ui
+            .button(if time_trial_run.is_some() {
+                "Time Trial: On"
+            } else {
+                "Time Trial: Off"
+            })
+            .kbgp_navigation()
+            .clicked()
+        {
+            if time_trial_run.is_some() {
+                commands.remove_resource::<TimeTrialRun>();
+            } else {
+                commands.insert_resource(TimeTrialRun::default());
+            }
+        }
         let mut response = ui
             .button("Back To Menu")
             .kbgp_navigation()
@@ -141,12 +332,18 @@ fn level_select_menu(
         egui::ScrollArea::vertical().show(ui, |ui| {
             let level_index = some_or!(level_index; return);
             for (index, level) in level_index.iter().enumerate() {
+                let is_available = index < level_progress.num_levels_available;
                 let mut response = ui
                     .add_enabled(
-                        index < level_progress.num_levels_available,
-                        egui::Button::new(format_level_name(&level.filename)),
+                        is_available,
+                        egui::Button::new(level_info_cache.display_title(&level.filename)),
                     )
                     .kbgp_navigation();
+                if let Some(level_info) = level_info_cache.get(&level.filename) {
+                    if is_available {
+                        level_info_label(ui, &level_info.subtitle, level_info.par_time);
+                    }
+                }
                 if index + 1 == level_progress.num_levels_available {
                     response = response.kbgp_focus_label(FocusLabel::NextLevel);
                 }
@@ -162,9 +359,66 @@ fn level_select_menu(
     });
 }
 
+fn level_info_label(ui: &mut egui::Ui, subtitle: &str, par_time: Option<f32>) {
+    let text = match (subtitle.trim(), par_time) {
+        ("", None) => return,
+        (subtitle, None) => subtitle.to_owned(),
+        ("", Some(par_time)) => format!("Par: {:.0}s", par_time),
+        (subtitle, Some(par_time)) => format!("{} (Par: {:.0}s)", subtitle, par_time),
+    };
+    ui.label(
+        egui::RichText::new(text)
+            .small()
+            .color(egui::Color32::WHITE)
+            .background_color(egui::Color32::BLACK),
+    );
+}
+
+fn level_splash_menu(
+    mut egui_context: ResMut<EguiContext>,
+    mut state: ResMut<State<AppState>>,
+    level_progress: Res<LevelProgress>,
+    level_info_cache: Res<LevelInfoCache>,
+) {
+    let current_level = some_or!(level_progress.current_level.as_ref(); return);
+    let level_info = level_info_cache.get(current_level);
+    menu_layout(egui_context.ctx_mut(), |ui| {
+        ui.label(
+            egui::RichText::new(level_info_cache.display_title(current_level))
+                .color(egui::Color32::WHITE)
+                .background_color(egui::Color32::BLACK)
+                .text_style(egui::TextStyle::Heading),
+        );
+        if let Some(level_info) = level_info {
+            level_info_label(ui, &level_info.subtitle, level_info.par_time);
+            if let Some(hint) = level_info.hint_for_retries(level_progress.retries) {
+                ui.add_space(8.0);
+                ui.label(
+                    egui::RichText::new(format!("Hint: {}", hint))
+                        .color(egui::Color32::YELLOW)
+                        .background_color(egui::Color32::BLACK),
+                );
+            }
+        }
+        ui.add_space(8.0);
+        if ui
+            .button("Start")
+            .kbgp_navigation()
+            .kbgp_initial_focus()
+            .clicked()
+            || ui.kbgp_user_action() == Some(MenuActionForKbgp)
+        {
+            state.set(AppState::Game).unwrap();
+            ui.kbgp_clear_input();
+        }
+    });
+}
+
 fn pause_menu(
     mut egui_context: ResMut<EguiContext>,
     mut state: ResMut<State<AppState>>,
+    checkpoint_snapshot: Option<Res<CheckpointSnapshot>>,
+    mut restore_checkpoint_writer: EventWriter<RestoreCheckpoint>,
     #[cfg(not(target_arch = "wasm32"))] mut exit: EventWriter<bevy::app::AppExit>,
 ) {
     menu_layout(egui_context.ctx_mut(), |ui| {
@@ -177,7 +431,19 @@ fn pause_menu(
         {
             state.set(AppState::Game).unwrap();
         }
-        if ui.button("Retry").kbgp_navigation().clicked() {
+        if checkpoint_snapshot.is_some() {
+            if ui
+                .button("Retry From Checkpoint")
+                .kbgp_navigation()
+                .clicked()
+            {
+                restore_checkpoint_writer.send(RestoreCheckpoint);
+                state.set(AppState::Game).unwrap();
+            }
+            if ui.button("Restart Level").kbgp_navigation().clicked() {
+                state.set(AppState::LoadLevel).unwrap();
+            }
+        } else if ui.button("Retry").kbgp_navigation().clicked() {
             state.set(AppState::LoadLevel).unwrap();
         }
         if ui
@@ -211,6 +477,7 @@ fn level_completed_menu(
     mut egui_context: ResMut<EguiContext>,
     mut state: ResMut<State<AppState>>,
     level_progress: Res<LevelProgress>,
+    level_info_cache: Res<LevelInfoCache>,
     #[cfg(not(target_arch = "wasm32"))] mut exit: EventWriter<bevy::app::AppExit>,
 ) {
     menu_layout(egui_context.ctx_mut(), |ui| {
@@ -219,15 +486,21 @@ fn level_completed_menu(
         }
         let just_completed = level_progress.just_completed.as_ref().unwrap();
         ui.label(
-            egui::RichText::new(format!("Finished {:?}", format_level_name(just_completed)))
-                .color(egui::Color32::WHITE)
-                .background_color(egui::Color32::BLACK)
-                .text_style(egui::TextStyle::Heading),
+            egui::RichText::new(format!(
+                "Finished {:?}",
+                level_info_cache.display_title(just_completed)
+            ))
+            .color(egui::Color32::WHITE)
+            .background_color(egui::Color32::BLACK)
+            .text_style(egui::TextStyle::Heading),
         );
         ui.add_space(8.0);
         if let Some(current_level) = &level_progress.current_level {
             if ui
-                .button(format!("Next Level: {}", format_level_name(current_level)))
+                .button(format!(
+                    "Next Level: {}",
+                    level_info_cache.display_title(current_level)
+                ))
                 .kbgp_navigation()
                 .kbgp_initial_focus()
                 .clicked()
@@ -278,12 +551,27 @@ fn level_completed_menu(
 fn game_over_menu(
     mut egui_context: ResMut<EguiContext>,
     mut state: ResMut<State<AppState>>,
+    survival_run: Option<Res<SurvivalRun>>,
+    pkv: Res<PkvStore>,
     #[cfg(not(target_arch = "wasm32"))] mut exit: EventWriter<bevy::app::AppExit>,
 ) {
     menu_layout(egui_context.ctx_mut(), |ui| {
         if ui.kbgp_user_action() == Some(MenuActionForKbgp) {
             ui.kbgp_set_focus_label(FocusLabel::Exit);
         }
+        if let Some(survival_run) = &survival_run {
+            ui.label(
+                egui::RichText::new(format!(
+                    "Files Downloaded: {}\nBest: {}",
+                    survival_run.files_completed,
+                    survival_high_score(&pkv),
+                ))
+                .color(egui::Color32::WHITE)
+                .background_color(egui::Color32::BLACK)
+                .text_style(egui::TextStyle::Heading),
+            );
+            ui.add_space(8.0);
+        }
         if ui
             .button("Retry")
             .kbgp_navigation()
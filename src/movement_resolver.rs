@@ -3,15 +3,16 @@ use bevy::utils::HashSet;
 use bevy_rapier2d::pipeline::CollisionEvent;
 use bevy_rapier2d::prelude::Velocity;
 
+use crate::game_clock::GameClock;
 use crate::global_types::{AppState, GameSystemLabel, GrabStatus};
 
 pub struct MovementResolverPlugin;
 
 impl Plugin for MovementResolverPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(maintain_contact_lists);
         app.add_system_set({
             SystemSet::on_update(AppState::Game)
+                .with_system(maintain_contact_lists)
                 .with_system(apply_movement.label(GameSystemLabel::ApplyMovement))
         });
     }
@@ -24,6 +25,12 @@ pub struct MoveController {
     pub max_speed: f32,
     pub impulse_exponent: f32,
     pub impulse_coefficient: f32,
+    /// Scales `max_speed`. Set by hazard zones.
+    pub speed_factor: f32,
+    /// Scales `impulse_coefficient`. Set by hazard zones.
+    pub traction: f32,
+    /// Movement is relative to this. Set by conveyors.
+    pub carried_velocity: Vec2,
 }
 
 impl Default for MoveController {
@@ -34,6 +41,9 @@ impl Default for MoveController {
             max_speed: 10.0,
             impulse_exponent: 4.0,
             impulse_coefficient: 200.0,
+            speed_factor: 1.0,
+            traction: 1.0,
+            carried_velocity: Vec2::ZERO,
         }
     }
 }
@@ -65,7 +75,7 @@ fn maintain_contact_lists(
 }
 
 fn apply_movement(
-    time: Res<Time>,
+    game_clock: Res<GameClock>,
     mut query: Query<(Entity, &MoveController, &mut Transform, &mut Velocity)>,
     grabbers_query: Query<(Entity, &GrabStatus)>,
 ) {
@@ -81,7 +91,8 @@ fn apply_movement(
         .flatten()
         .collect();
     for (moving_entity, move_controller, mut transform, mut velocity) in query.iter_mut() {
-        let current_speed = velocity.linvel / move_controller.max_speed;
+        let max_speed = move_controller.max_speed * move_controller.speed_factor;
+        let current_speed = (velocity.linvel - move_controller.carried_velocity) / max_speed;
 
         // TODO: Use different impulses for accelerate, decelerate and turn
 
@@ -90,8 +101,9 @@ fn apply_movement(
         let orig_impulse_magnitude = impulse.length();
 
         // TODO: Implement a better way to finish the brake
-        if orig_impulse_magnitude < 0.4 {
-            velocity.linvel = move_controller.max_speed * move_controller.target_speed;
+        if orig_impulse_magnitude < 0.4 * move_controller.traction {
+            velocity.linvel =
+                move_controller.carried_velocity + max_speed * move_controller.target_speed;
         } else {
             let impulse = if 1.0 < orig_impulse_magnitude {
                 impulse.normalize()
@@ -99,8 +111,10 @@ fn apply_movement(
                 impulse.normalize() * orig_impulse_magnitude.powf(move_controller.impulse_exponent)
             };
 
-            let impulse =
-                time.delta().as_secs_f32() * move_controller.impulse_coefficient * impulse;
+            let impulse = game_clock.delta_seconds()
+                * move_controller.impulse_coefficient
+                * move_controller.traction
+                * impulse;
             velocity.linvel += impulse;
         }
 
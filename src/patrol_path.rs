@@ -0,0 +1,231 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_yoleck::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::game_clock::GameClock;
+use crate::global_types::AppState;
+
+pub struct PatrolPathPlugin;
+
+impl Plugin for PatrolPathPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(AppState::Game).with_system(follow_patrol_paths));
+    }
+}
+
+/// Embed in the data of entities that can patrol along a path, like routers and jammers. Entities
+/// with a path should not be dynamic bodies - the path moves their `Transform` directly.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatrolPath {
+    /// Relative to the entity's position, which is implicitly the first waypoint.
+    #[serde(default)]
+    pub waypoints: Vec<Vec2>,
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+    #[serde(default)]
+    pub mode: PatrolPathMode,
+}
+
+fn default_speed() -> f32 {
+    2.0
+}
+
+impl Default for PatrolPath {
+    fn default() -> Self {
+        Self {
+            waypoints: Vec::new(),
+            speed: default_speed(),
+            mode: Default::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PatrolPathMode {
+    /// Go back to the start from the last waypoint.
+    Loop,
+    /// Go through the waypoints backwards after reaching the last one.
+    PingPong,
+}
+
+impl Default for PatrolPathMode {
+    fn default() -> Self {
+        Self::Loop
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct PatrolPathFollower {
+    /// Absolute, including the starting position.
+    waypoints: Vec<Vec2>,
+    speed: f32,
+    mode: PatrolPathMode,
+    next_waypoint: usize,
+    is_going_backwards: bool,
+}
+
+const WAYPOINT_MARKER_COLOR: Color = Color::rgba(1.0, 1.0, 0.0, 0.5);
+
+impl PatrolPath {
+    /// Inserts a `PatrolPathFollower` if the path has any waypoints, or removes it if not. In the editor
+    /// it also marks the waypoints, so call `despawn_descendants` before it.
+    pub fn populate(&self, cmd: &mut EntityCommands, origin: Vec2, is_in_editor: bool) {
+        if self.waypoints.is_empty() {
+            cmd.remove::<PatrolPathFollower>();
+            return;
+        }
+        if is_in_editor {
+            cmd.with_children(|commands| {
+                for waypoint in self.waypoints.iter() {
+                    commands.spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: WAYPOINT_MARKER_COLOR,
+                            custom_size: Some(Vec2::new(0.3, 0.3)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_translation(waypoint.extend(0.0)),
+                        ..Default::default()
+                    });
+                }
+            });
+        }
+        cmd.insert(PatrolPathFollower {
+            waypoints: std::iter::once(origin)
+                .chain(self.waypoints.iter().map(|waypoint| origin + *waypoint))
+                .collect(),
+            speed: self.speed,
+            mode: self.mode,
+            next_waypoint: 1,
+            is_going_backwards: false,
+        });
+    }
+
+    pub fn edit(&mut self, ui: &mut egui::Ui, id_source: &str) {
+        ui.collapsing("Patrol Path", |ui| {
+            ui.label("Waypoints (relative to the starting position):");
+            let mut to_remove = None;
+            for (index, waypoint) in self.waypoints.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut waypoint.x)
+                            .prefix("X:")
+                            .speed(0.05),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut waypoint.y)
+                            .prefix("Y:")
+                            .speed(0.05),
+                    );
+                    if ui.small_button("X").clicked() {
+                        to_remove = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = to_remove {
+                self.waypoints.remove(index);
+            }
+            if ui.button("Add Waypoint").clicked() {
+                let new_waypoint = self.waypoints.last().copied().unwrap_or_default() + Vec2::X;
+                self.waypoints.push(new_waypoint);
+            }
+            if self.waypoints.is_empty() {
+                return;
+            }
+            ui.add(egui::Slider::new(&mut self.speed, 0.1..=10.0).prefix("Speed: "));
+            egui::ComboBox::from_id_source((id_source, "patrol-path-mode"))
+                .selected_text(format!("Mode: {:?}", self.mode))
+                .show_ui(ui, |ui| {
+                    for option in [PatrolPathMode::Loop, PatrolPathMode::PingPong] {
+                        ui.selectable_value(&mut self.mode, option, format!("{:?}", option));
+                    }
+                });
+        });
+    }
+}
+
+impl PatrolPathFollower {
+    fn advance_waypoint(&mut self) {
+        let last = self.waypoints.len() - 1;
+        match self.mode {
+            PatrolPathMode::Loop => {
+                self.next_waypoint = (self.next_waypoint + 1) % self.waypoints.len();
+            }
+            PatrolPathMode::PingPong => {
+                if self.is_going_backwards && self.next_waypoint == 0 {
+                    self.is_going_backwards = false;
+                } else if !self.is_going_backwards && self.next_waypoint == last {
+                    self.is_going_backwards = true;
+                }
+                if self.is_going_backwards {
+                    self.next_waypoint -= 1;
+                } else {
+                    self.next_waypoint += 1;
+                }
+            }
+        }
+    }
+}
+
+fn follow_patrol_paths(
+    game_clock: Res<GameClock>,
+    mut query: Query<(&mut PatrolPathFollower, &mut Transform)>,
+) {
+    for (mut path_follower, mut transform) in query.iter_mut() {
+        let mut distance_left = path_follower.speed * game_clock.delta_seconds();
+        // Bounded, in case all the waypoints are at the same spot.
+        for _ in 0..path_follower.waypoints.len() {
+            let target = path_follower.waypoints[path_follower.next_waypoint];
+            let position = transform.translation.truncate();
+            let distance_to_target = position.distance(target);
+            if distance_left < distance_to_target {
+                let new_position = position + distance_left * (target - position).normalize();
+                transform.translation = new_position.extend(transform.translation.z);
+                break;
+            }
+            transform.translation = target.extend(transform.translation.z);
+            distance_left -= distance_to_target;
+            path_follower.advance_waypoint();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn follower(mode: PatrolPathMode) -> PatrolPathFollower {
+        PatrolPathFollower {
+            waypoints: vec![Vec2::ZERO, Vec2::X, Vec2::Y],
+            speed: 1.0,
+            mode,
+            next_waypoint: 1,
+            is_going_backwards: false,
+        }
+    }
+
+    fn waypoint_sequence(mut follower: PatrolPathFollower, steps: usize) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                follower.advance_waypoint();
+                follower.next_waypoint
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loop_wraps_to_the_start() {
+        assert_eq!(
+            waypoint_sequence(follower(PatrolPathMode::Loop), 5),
+            vec![2, 0, 1, 2, 0]
+        );
+    }
+
+    #[test]
+    fn ping_pong_turns_around_at_both_ends() {
+        assert_eq!(
+            waypoint_sequence(follower(PatrolPathMode::PingPong), 6),
+            vec![2, 1, 0, 1, 2, 1]
+        );
+    }
+}
@@ -27,29 +27,89 @@ impl Plugin for PlayableAreaPlugin {
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct PlayableArea {
     #[serde(default)]
-    position: Vec2,
+    pub(crate) position: Vec2,
     #[serde(default = "default_size")]
-    size: Vec2,
+    pub(crate) size: Vec2,
+    #[serde(default)]
+    pub(crate) shape: PlayableAreaShape,
 }
 
 fn default_size() -> Vec2 {
     Vec2::new(1.0, 1.0)
 }
 
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum PlayableAreaShape {
+    /// Uses the playable area's `size`.
+    Rectangle,
+    /// Relative to the playable area's position. Can be concave.
+    Polygon { outline: Vec<Vec2> },
+}
+
+impl Default for PlayableAreaShape {
+    fn default() -> Self {
+        Self::Rectangle
+    }
+}
+
+impl PlayableArea {
+    /// Pairs of points, each a segment of the boundary.
+    fn boundary_segments(&self) -> Vec<(Vec2, Vec2)> {
+        let outline = match &self.shape {
+            PlayableAreaShape::Rectangle => {
+                let half_size = 0.5 * self.size;
+                vec![
+                    Vec2::new(-half_size.x, -half_size.y),
+                    Vec2::new(half_size.x, -half_size.y),
+                    Vec2::new(half_size.x, half_size.y),
+                    Vec2::new(-half_size.x, half_size.y),
+                ]
+            }
+            PlayableAreaShape::Polygon { outline } => outline.clone(),
+        };
+        outline
+            .iter()
+            .copied()
+            .zip(outline.iter().copied().cycle().skip(1))
+            .collect()
+    }
+
+    fn bounds_size(&self) -> Vec2 {
+        match &self.shape {
+            PlayableAreaShape::Rectangle => self.size,
+            PlayableAreaShape::Polygon { outline } => {
+                let min = outline.iter().copied().reduce(Vec2::min);
+                let max = outline.iter().copied().reduce(Vec2::max);
+                if let (Some(min), Some(max)) = (min, max) {
+                    max - min
+                } else {
+                    Vec2::ZERO
+                }
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct PlayableAreaBounds {
+    pub size: Vec2,
+}
+
 fn populate(mut populate: YoleckPopulate<PlayableArea>) {
     populate.populate(|_ctx, data, mut cmd| {
         cmd.despawn_descendants();
         cmd.insert_bundle(TransformBundle::from_transform(
             Transform::from_translation(data.position.extend(-0.1)),
         ));
+        cmd.insert(PlayableAreaBounds {
+            size: data.bounds_size(),
+        });
         cmd.insert(bevy_yoleck::vpeol::YoleckWillContainClickableChildren);
         cmd.with_children(|commands| {
-            for (offset_direction, size) in [
-                (-Vec2::Y, Vec2::new(data.size.x + 0.5, 0.5)),
-                (Vec2::Y, Vec2::new(data.size.x + 0.5, 0.5)),
-                (-Vec2::X, Vec2::new(0.5, data.size.y + 0.5)),
-                (Vec2::X, Vec2::new(0.5, data.size.y + 0.5)),
-            ] {
+            for (start, end) in data.boundary_segments() {
+                let vec = end - start;
+                // Extending the segments so that they overlap at the corners
+                let size = Vec2::new(vec.length() + 0.5, 0.5);
                 let mut cmd = commands.spawn();
                 cmd.insert_bundle(SpriteBundle {
                     sprite: Sprite {
@@ -57,9 +117,8 @@ fn populate(mut populate: YoleckPopulate<PlayableArea>) {
                         custom_size: Some(size),
                         ..Default::default()
                     },
-                    transform: Transform::from_translation(
-                        (offset_direction * 0.5 * data.size).extend(0.0),
-                    ),
+                    transform: Transform::from_translation((0.5 * (start + end)).extend(0.0))
+                        .with_rotation(Quat::from_rotation_z(vec.y.atan2(vec.x))),
                     ..Default::default()
                 });
                 cmd.insert(CameraInclude);
@@ -72,17 +131,67 @@ fn populate(mut populate: YoleckPopulate<PlayableArea>) {
 
 fn edit(mut edit: YoleckEdit<PlayableArea>) {
     edit.edit(|_ctx, data, ui| {
+        let is_polygon = matches!(data.shape, PlayableAreaShape::Polygon { .. });
         ui.horizontal(|ui| {
-            ui.add(
-                egui::DragValue::new(&mut data.size.x)
-                    .prefix("Width:")
-                    .speed(0.05),
-            );
-            ui.add(
-                egui::DragValue::new(&mut data.size.y)
-                    .prefix("Height:")
-                    .speed(0.05),
-            );
+            ui.label("Shape:");
+            if ui.selectable_label(!is_polygon, "Rectangle").clicked() && is_polygon {
+                data.shape = PlayableAreaShape::Rectangle;
+            }
+            if ui.selectable_label(is_polygon, "Polygon").clicked() && !is_polygon {
+                data.shape = PlayableAreaShape::Polygon {
+                    outline: data
+                        .boundary_segments()
+                        .into_iter()
+                        .map(|(start, _)| start)
+                        .collect(),
+                };
+            }
         });
+        match &mut data.shape {
+            PlayableAreaShape::Rectangle => {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut data.size.x)
+                            .prefix("Width:")
+                            .speed(0.05),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut data.size.y)
+                            .prefix("Height:")
+                            .speed(0.05),
+                    );
+                });
+            }
+            PlayableAreaShape::Polygon { outline } => {
+                ui.label("Outline (relative to the area's position):");
+                let mut to_remove = None;
+                let mut to_split = None;
+                for (index, vertex) in outline.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut vertex.x).prefix("X:").speed(0.05));
+                        ui.add(egui::DragValue::new(&mut vertex.y).prefix("Y:").speed(0.05));
+                        if ui
+                            .small_button("+")
+                            .on_hover_text("Split next edge")
+                            .clicked()
+                        {
+                            to_split = Some(index);
+                        }
+                        if ui.small_button("X").clicked() {
+                            to_remove = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = to_split {
+                    let next = outline[(index + 1) % outline.len()];
+                    outline.insert(index + 1, 0.5 * (outline[index] + next));
+                }
+                if let Some(index) = to_remove {
+                    if 3 < outline.len() {
+                        outline.remove(index);
+                    }
+                }
+            }
+        }
     });
 }
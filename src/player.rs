@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use bevy::utils::HashSet;
 use bevy_rapier2d::prelude::*;
 use bevy_rapier2d::rapier::prelude::{JointAxesMask, JointAxis};
 use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
@@ -6,12 +7,15 @@ use bevy_yoleck::{egui, YoleckEdit, YoleckExtForApp, YoleckPopulate, YoleckTypeH
 use ezinput::prelude::{InputView, PressStateExt};
 use serde::{Deserialize, Serialize};
 
+use crate::game_clock::GameClock;
 use crate::global_types::{
-    AppState, DownloadProgress, GrabStatus, Grabbable, InputBinding, IsPlayer, WifiClient,
+    AppState, DownloadProgress, GrabMode, GrabReleased, GrabStarted, GrabStatus, GrabTooHeavy,
+    Grabbable, InputBinding, IsPlayer, SpawnedDuringLevel, WifiClient,
 };
 use crate::loading::GameAssets;
 use crate::movement_resolver::MoveController;
 use crate::player_control::PlayerControl;
+use crate::utils::some_or;
 
 pub struct PlayerPlugin;
 
@@ -27,10 +31,16 @@ impl Plugin for PlayerPlugin {
                 }))
                 .edit_with(edit)
         });
+        app.add_event::<GrabStarted>();
+        app.add_event::<GrabReleased>();
+        app.add_event::<GrabTooHeavy>();
         app.add_system_set({
             SystemSet::on_update(AppState::Game)
                 .with_system(control_grabbing_initiation)
                 .with_system(handle_grabbing_taking_hold)
+                .with_system(update_lifted_entities)
+                .with_system(show_too_heavy_popups.after(handle_grabbing_taking_hold))
+                .with_system(fade_too_heavy_popups)
         });
     }
 }
@@ -38,9 +48,9 @@ impl Plugin for PlayerPlugin {
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Player {
     #[serde(default)]
-    position: Vec2,
+    pub(crate) position: Vec2,
     #[serde(default)]
-    rotation: f32,
+    pub(crate) rotation: f32,
 }
 
 fn populate(mut populate: YoleckPopulate<Player>, game_assets: Res<GameAssets>) {
@@ -86,12 +96,49 @@ fn edit(mut edit: YoleckEdit<Player>) {
     });
 }
 
+pub fn spawn_hands(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    grabber_entity: Entity,
+) -> Entity {
+    let mut cmd = commands.spawn();
+    let transform = Transform::from_translation(Vec3::new(0.0, 0.4, 0.1));
+    cmd.insert_bundle(SpriteBundle {
+        transform,
+        global_transform: transform.into(),
+        sprite: Sprite {
+            custom_size: Some(Vec2::new(1.0, 1.0)),
+            ..Default::default()
+        },
+        texture: game_assets.hands.clone(),
+        ..Default::default()
+    });
+    cmd.insert(Collider::cuboid(0.4, 0.2));
+    cmd.insert(Sensor(true));
+
+    let hands_entity = cmd.id();
+    commands.entity(grabber_entity).add_child(hands_entity);
+    hands_entity
+}
+
+/// Goes on the hands entity, to connect it with the entity being held.
+pub fn holding_joint(grabbed_entity: Entity, grabbable: &Grabbable) -> ImpulseJoint {
+    let mut joint = GenericJoint::new(JointAxesMask::empty());
+    joint.set_local_anchor2(grabbable.anchor);
+    joint.set_motor(JointAxis::X, 0.0, 0.0, grabbable.stiffness, 0.0);
+    joint.set_motor(JointAxis::Y, 0.0, 0.0, grabbable.stiffness, 0.0);
+    joint.set_motor(JointAxis::AngX, 0.0, 0.0, grabbable.stiffness, 0.0);
+    ImpulseJoint::new(grabbed_entity, joint)
+}
+
 fn control_grabbing_initiation(
-    time: Res<Time>,
+    game_clock: Res<GameClock>,
     input_views: Query<&InputView<InputBinding>>,
-    mut grabbers_query: Query<(Entity, &mut GrabStatus)>,
+    // Zombies that grab have their own controls.
+    mut grabbers_query: Query<(Entity, &mut GrabStatus), With<PlayerControl>>,
     mut commands: Commands,
     game_assets: Res<GameAssets>,
+    mut grab_released_writer: EventWriter<GrabReleased>,
 ) {
     let should_grab = input_views
         .iter()
@@ -102,26 +149,7 @@ fn control_grabbing_initiation(
             GrabStatus::NoGrab => {
                 if should_grab {
                     GrabStatus::Reaching {
-                        hands_entity: {
-                            let mut cmd = commands.spawn();
-                            let transform = Transform::from_translation(Vec3::new(0.0, 0.4, 0.1));
-                            cmd.insert_bundle(SpriteBundle {
-                                transform,
-                                global_transform: transform.into(),
-                                sprite: Sprite {
-                                    custom_size: Some(Vec2::new(1.0, 1.0)),
-                                    ..Default::default()
-                                },
-                                texture: game_assets.hands.clone(),
-                                ..Default::default()
-                            });
-                            cmd.insert(Collider::cuboid(0.4, 0.2));
-                            cmd.insert(Sensor(true));
-
-                            let hands_entity = cmd.id();
-                            commands.entity(grabber_entity).add_child(hands_entity);
-                            hands_entity
-                        },
+                        hands_entity: spawn_hands(&mut commands, &game_assets, grabber_entity),
                         how_long: 0.0,
                     }
                 } else {
@@ -140,7 +168,7 @@ fn control_grabbing_initiation(
                 how_long,
             } => {
                 if should_grab {
-                    let how_long = how_long + time.delta_seconds();
+                    let how_long = how_long + game_clock.delta_seconds();
                     if how_long < 0.2 {
                         GrabStatus::Reaching {
                             hands_entity,
@@ -166,6 +194,10 @@ fn control_grabbing_initiation(
                     }
                 } else {
                     commands.entity(hands_entity).despawn_recursive();
+                    grab_released_writer.send(GrabReleased {
+                        grabber: grabber_entity,
+                        grabbed: other,
+                    });
                     GrabStatus::NoGrab
                 }
             }
@@ -174,11 +206,14 @@ fn control_grabbing_initiation(
 }
 
 fn handle_grabbing_taking_hold(
-    mut grabbers_query: Query<(&GlobalTransform, &mut GrabStatus)>,
-    grabbable_query: Query<(Entity, &GlobalTransform), With<Grabbable>>,
+    rapier_context: Res<RapierContext>,
+    mut grabbers_query: Query<(Entity, &GlobalTransform, &mut GrabStatus), With<PlayerControl>>,
+    grabbable_query: Query<(Entity, &GlobalTransform, &Grabbable)>,
     mut commands: Commands,
+    mut grab_started_writer: EventWriter<GrabStarted>,
+    mut grab_too_heavy_writer: EventWriter<GrabTooHeavy>,
 ) {
-    for (grabber_transform, mut grab_status) in grabbers_query.iter_mut() {
+    for (grabber_entity, grabber_transform, mut grab_status) in grabbers_query.iter_mut() {
         let hands_entity = if let GrabStatus::Reaching {
             hands_entity,
             how_long,
@@ -191,32 +226,146 @@ fn handle_grabbing_taking_hold(
         } else {
             continue;
         };
-        if let Some((grabbable_entity, _)) = grabbable_query
+        if let Some((grabbable_entity, grabbable, _)) = grabbable_query
             .iter()
-            .filter_map(|(grabbable_entity, grabbable_transform)| {
+            .filter_map(|(grabbable_entity, grabbable_transform, grabbable)| {
                 let vec = (grabber_transform.rotation.inverse()
                     * (grabbable_transform.translation - grabber_transform.translation))
                     .truncate();
                 if vec.x.abs() <= 1.0 && 0.0 <= vec.y && vec.y <= 1.0 {
-                    Some((grabbable_entity, vec))
+                    Some((grabbable_entity, grabbable, vec))
                 } else {
                     None
                 }
             })
-            .min_by_key(|(_, vec)| float_ord::FloatOrd(vec.x.abs() + vec.y * 5.0))
+            .min_by_key(|(_, _, vec)| float_ord::FloatOrd(vec.x.abs() + vec.y * 5.0))
         {
-            let mut joint = GenericJoint::new(JointAxesMask::empty());
-            joint.set_local_anchor2(Vec2::new(0.0, 0.5));
-            joint.set_motor(JointAxis::X, 0.0, 0.0, 1000.0, 0.0);
-            joint.set_motor(JointAxis::Y, 0.0, 0.0, 1000.0, 0.0);
-            joint.set_motor(JointAxis::AngX, 0.0, 0.0, 1000.0, 0.0);
+            let mass = rapier_context
+                .entity2body()
+                .get(&grabbable_entity)
+                .and_then(|body_handle| rapier_context.bodies.get(*body_handle))
+                .map_or(0.0, |body| body.mass());
+            if grabbable.max_mass < mass {
+                commands.entity(hands_entity).despawn_recursive();
+                *grab_status = GrabStatus::GrabFailed;
+                grab_too_heavy_writer.send(GrabTooHeavy {
+                    grabber: grabber_entity,
+                    grabbed: grabbable_entity,
+                });
+                continue;
+            }
             commands
                 .entity(hands_entity)
-                .insert(ImpulseJoint::new(grabbable_entity, joint));
+                .insert(holding_joint(grabbable_entity, grabbable));
             *grab_status = GrabStatus::Holding {
                 hands_entity,
                 other: grabbable_entity,
             };
+            grab_started_writer.send(GrabStarted {
+                grabber: grabber_entity,
+                grabbed: grabbable_entity,
+            });
+        }
+    }
+}
+
+/// Marks entities that are carried with their collisions disabled.
+#[derive(Component)]
+struct Lifted;
+
+/// Decided by the grab statuses rather than by the grab events, so that it also follows grabs
+/// restored by rewinding.
+fn update_lifted_entities(
+    grabbers_query: Query<&GrabStatus>,
+    grabbables_query: Query<(Entity, &Grabbable, Option<&Lifted>)>,
+    mut commands: Commands,
+) {
+    let held_entities: HashSet<Entity> = grabbers_query
+        .iter()
+        .filter_map(|grab_status| {
+            if let GrabStatus::Holding { other, .. } = grab_status {
+                Some(*other)
+            } else {
+                None
+            }
+        })
+        .collect();
+    for (entity, grabbable, lifted) in grabbables_query.iter() {
+        let should_be_lifted = grabbable.mode == GrabMode::Lift && held_entities.contains(&entity);
+        if should_be_lifted && lifted.is_none() {
+            commands
+                .entity(entity)
+                .insert(Lifted)
+                .insert(SolverGroups::new(0, 0));
+        } else if !should_be_lifted && lifted.is_some() {
+            commands
+                .entity(entity)
+                .remove::<Lifted>()
+                .remove::<SolverGroups>();
+        }
+    }
+}
+
+#[derive(Component)]
+struct TooHeavyPopup {
+    time_left: f32,
+}
+
+const TOO_HEAVY_POPUP_DURATION: f32 = 1.0;
+
+fn show_too_heavy_popups(
+    mut grab_too_heavy_reader: EventReader<GrabTooHeavy>,
+    grabbed_query: Query<&GlobalTransform>,
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+) {
+    for event in grab_too_heavy_reader.iter() {
+        let grabbed_transform = some_or!(grabbed_query.get(event.grabbed).ok(); continue);
+        let mut cmd = commands.spawn();
+        cmd.insert_bundle(Text2dBundle {
+            text: Text::with_section(
+                "Too heavy!",
+                TextStyle {
+                    font: game_assets.font.clone(),
+                    font_size: 72.0,
+                    color: Color::ORANGE_RED,
+                },
+                TextAlignment {
+                    vertical: VerticalAlign::Center,
+                    horizontal: HorizontalAlign::Center,
+                },
+            ),
+            transform: Transform {
+                translation: grabbed_transform.translation.truncate().extend(10.0) + 0.8 * Vec3::Y,
+                rotation: Default::default(),
+                scale: Vec3::new(0.01, 0.01, 1.0),
+            },
+            ..Default::default()
+        });
+        cmd.insert(TooHeavyPopup {
+            time_left: TOO_HEAVY_POPUP_DURATION,
+        });
+        cmd.insert(SpawnedDuringLevel);
+    }
+}
+
+fn fade_too_heavy_popups(
+    game_clock: Res<GameClock>,
+    mut query: Query<(Entity, &mut TooHeavyPopup, &mut Transform, &mut Text)>,
+    mut commands: Commands,
+) {
+    for (entity, mut popup, mut transform, mut text) in query.iter_mut() {
+        popup.time_left -= game_clock.delta_seconds();
+        if popup.time_left <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.translation.y += 0.5 * game_clock.delta_seconds();
+        for section in text.sections.iter_mut() {
+            section
+                .style
+                .color
+                .set_a(popup.time_left / TOO_HEAVY_POPUP_DURATION);
         }
     }
 }
@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
+use bevy_yoleck::{egui, YoleckEdit, YoleckExtForApp, YoleckPopulate, YoleckTypeHandler};
+use serde::{Deserialize, Serialize};
+
+use crate::global_types::{GrabMode, Grabbable, SignalAttenuator};
+
+pub struct PropPlugin;
+
+impl Plugin for PropPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<Prop>::new("Prop")
+                .populate_with(populate)
+                .with(yoleck_vpeol_position_edit_adapter(|prop: &mut Prop| {
+                    YoleckVpeolTransform2dProjection {
+                        translation: &mut prop.position,
+                    }
+                }))
+                .edit_with(edit)
+        });
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Prop {
+    #[serde(default)]
+    position: Vec2,
+    #[serde(default)]
+    rotation: f32,
+    #[serde(default)]
+    kind: PropKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PropKind {
+    Crate,
+    Desk,
+    Chair,
+    FilingCabinet,
+}
+
+impl Default for PropKind {
+    fn default() -> Self {
+        Self::Crate
+    }
+}
+
+/// Desks can just barely be dragged, but filing cabinets are too heavy to move.
+const HEAVY_FURNITURE_MAX_MASS: f32 = 20.0;
+
+impl PropKind {
+    const ALL: [PropKind; 4] = [
+        PropKind::Crate,
+        PropKind::Desk,
+        PropKind::Chair,
+        PropKind::FilingCabinet,
+    ];
+
+    fn size(&self) -> Vec2 {
+        match self {
+            PropKind::Crate => Vec2::new(0.8, 0.8),
+            PropKind::Desk => Vec2::new(1.6, 0.8),
+            PropKind::Chair => Vec2::new(0.6, 0.6),
+            PropKind::FilingCabinet => Vec2::new(0.6, 0.9),
+        }
+    }
+
+    fn collider(&self) -> Collider {
+        let size = self.size();
+        match self {
+            PropKind::Chair => Collider::ball(0.5 * size.x),
+            _ => Collider::cuboid(0.5 * size.x, 0.5 * size.y),
+        }
+    }
+
+    fn density(&self) -> f32 {
+        match self {
+            PropKind::Crate => 8.0,
+            PropKind::Desk => 15.0,
+            PropKind::Chair => 4.0,
+            PropKind::FilingCabinet => 40.0,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PropKind::Crate => Color::rgb(0.6, 0.4, 0.2),
+            PropKind::Desk => Color::rgb(0.8, 0.65, 0.45),
+            PropKind::Chair => Color::rgb(0.2, 0.25, 0.5),
+            PropKind::FilingCabinet => Color::rgb(0.5, 0.55, 0.6),
+        }
+    }
+
+    /// Small furniture is carried over everything, big furniture is dragged around.
+    fn grabbable(&self) -> Grabbable {
+        match self {
+            PropKind::Crate | PropKind::Chair => Grabbable {
+                stiffness: 500.0,
+                anchor: Vec2::new(0.0, 0.6),
+                mode: GrabMode::Lift,
+                max_mass: 6.0,
+            },
+            PropKind::Desk | PropKind::FilingCabinet => Grabbable {
+                anchor: Vec2::new(0.0, 0.5 + 0.5 * self.size().y),
+                max_mass: HEAVY_FURNITURE_MAX_MASS,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Metal furniture gets in the way of the WiFi signal.
+    fn signal_attenuator(&self) -> Option<SignalAttenuator> {
+        match self {
+            PropKind::FilingCabinet => Some(SignalAttenuator {
+                radius: 0.5,
+                signal_left: 0.6,
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn populate(mut populate: YoleckPopulate<Prop>) {
+    populate.populate(|_ctx, data, mut cmd| {
+        cmd.insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: data.kind.color(),
+                custom_size: Some(data.kind.size()),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        cmd.insert_bundle(TransformBundle::from_transform(
+            Transform::from_translation(data.position.extend(0.0))
+                .with_rotation(Quat::from_rotation_z(data.rotation)),
+        ));
+        cmd.insert(RigidBody::Dynamic);
+        cmd.insert(Damping {
+            linear_damping: 2.0,
+            angular_damping: 2.0,
+        });
+        cmd.insert(data.kind.collider());
+        cmd.insert(ColliderMassProperties::Density(data.kind.density()));
+        cmd.insert(Velocity::default());
+        cmd.insert(data.kind.grabbable());
+        if let Some(signal_attenuator) = data.kind.signal_attenuator() {
+            cmd.insert(signal_attenuator);
+        } else {
+            cmd.remove::<SignalAttenuator>();
+        }
+    });
+}
+
+fn edit(mut edit: YoleckEdit<Prop>) {
+    edit.edit(|_, data, ui| {
+        use std::f32::consts::{FRAC_PI_8, PI};
+        egui::ComboBox::from_id_source("prop-kind")
+            .selected_text(format!("Kind: {:?}", data.kind))
+            .show_ui(ui, |ui| {
+                for option in PropKind::ALL {
+                    ui.selectable_value(&mut data.kind, option, format!("{:?}", option));
+                }
+            });
+        ui.add({
+            egui::Slider::new(&mut data.rotation, PI..=-PI)
+                .prefix("Angle: ")
+                .step_by(FRAC_PI_8 as f64)
+        });
+    });
+}
@@ -0,0 +1,242 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{RigidBody, Velocity};
+use bevy_yoleck::YoleckManaged;
+use ezinput::prelude::{InputView, PressStateExt};
+
+use crate::checkpoint::RestoreCheckpoint;
+use crate::game_clock::GameClock;
+use crate::global_types::{
+    AppState, DownloadProgress, GrabReleased, GrabStarted, GrabStatus, Grabbable, InputBinding,
+    SpawnedDuringLevel,
+};
+use crate::level_info::{default_rewind_budget, LevelRewindBudget};
+use crate::loading::GameAssets;
+use crate::patrol_path::PatrolPathFollower;
+use crate::player::{holding_joint, spawn_hands};
+use crate::utils::some_or;
+
+pub struct RewindPlugin;
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RewindStatus>();
+        app.add_system_set(SystemSet::on_enter(AppState::LoadLevel).with_system(reset_rewind));
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game)
+                .with_system(control_rewind)
+                .with_system(record_rewind_frame.after(control_rewind))
+        });
+        app.add_system(forget_frames_on_checkpoint_restore);
+    }
+}
+
+/// How far back rewinding can go, regardless of the budget.
+const MAX_REWIND_DURATION: f32 = 5.0;
+
+struct RewindFrame {
+    delta: f32,
+    bodies: Vec<(Entity, Transform, Velocity)>,
+    download_progresses: Vec<(Entity, DownloadProgress)>,
+    patrols: Vec<(Entity, Transform, PatrolPathFollower)>,
+    /// What each grabber was holding.
+    grabs: Vec<(Entity, Option<Entity>)>,
+}
+
+#[derive(Default)]
+pub struct RewindStatus {
+    /// `None` until the level's budget is known.
+    pub budget_left: Option<f32>,
+    pub is_rewinding: bool,
+    frames: VecDeque<RewindFrame>,
+    frames_duration: f32,
+    /// Rewind time that was not yet enough to pop the next frame.
+    unspent_time: f32,
+    /// Grabs are only restored once rewinding ends, so that the grab controls don't fight them.
+    pending_grabs: Option<Vec<(Entity, Option<Entity>)>>,
+}
+
+fn reset_rewind(mut rewind_status: ResMut<RewindStatus>, mut game_clock: ResMut<GameClock>) {
+    *rewind_status = RewindStatus::default();
+    game_clock.frozen = false;
+}
+
+fn forget_frames_on_checkpoint_restore(
+    mut reader: EventReader<RestoreCheckpoint>,
+    mut rewind_status: ResMut<RewindStatus>,
+) {
+    if reader.iter().next().is_some() {
+        rewind_status.frames.clear();
+        rewind_status.frames_duration = 0.0;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn record_rewind_frame(
+    game_clock: Res<GameClock>,
+    mut rewind_status: ResMut<RewindStatus>,
+    // Zombies spawned during the level are not managed by Yoleck, but still need to be rewound.
+    bodies_query: Query<
+        (Entity, &Transform, &Velocity, &RigidBody),
+        Or<(With<YoleckManaged>, With<SpawnedDuringLevel>)>,
+    >,
+    download_progress_query: Query<(Entity, &DownloadProgress)>,
+    patrols_query: Query<(Entity, &Transform, &PatrolPathFollower)>,
+    grabbers_query: Query<(Entity, &GrabStatus)>,
+) {
+    if rewind_status.is_rewinding || !game_clock.is_running() {
+        return;
+    }
+    let frame = RewindFrame {
+        delta: game_clock.delta_seconds(),
+        bodies: bodies_query
+            .iter()
+            .filter(|(_, _, _, rigid_body)| **rigid_body == RigidBody::Dynamic)
+            .map(|(entity, transform, velocity, _)| (entity, *transform, *velocity))
+            .collect(),
+        download_progresses: download_progress_query
+            .iter()
+            .map(|(entity, download_progress)| (entity, download_progress.clone()))
+            .collect(),
+        patrols: patrols_query
+            .iter()
+            .map(|(entity, transform, follower)| (entity, *transform, follower.clone()))
+            .collect(),
+        grabs: grabbers_query
+            .iter()
+            .map(|(entity, grab_status)| {
+                if let GrabStatus::Holding { other, .. } = grab_status {
+                    (entity, Some(*other))
+                } else {
+                    (entity, None)
+                }
+            })
+            .collect(),
+    };
+    rewind_status.frames_duration += frame.delta;
+    rewind_status.frames.push_back(frame);
+    while MAX_REWIND_DURATION < rewind_status.frames_duration {
+        if let Some(dropped) = rewind_status.frames.pop_front() {
+            rewind_status.frames_duration -= dropped.delta;
+        } else {
+            break;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn control_rewind(
+    time: Res<Time>,
+    input_views: Query<&InputView<InputBinding>>,
+    mut rewind_status: ResMut<RewindStatus>,
+    mut game_clock: ResMut<GameClock>,
+    budget_query: Query<&LevelRewindBudget>,
+    mut bodies_query: Query<(&mut Transform, &mut Velocity)>,
+    mut download_progress_query: Query<&mut DownloadProgress>,
+    mut patrols_query: Query<(&mut Transform, &mut PatrolPathFollower), Without<Velocity>>,
+    mut grabbers_query: Query<&mut GrabStatus>,
+    grabbables_query: Query<&Grabbable>,
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut grab_started_writer: EventWriter<GrabStarted>,
+    mut grab_released_writer: EventWriter<GrabReleased>,
+) {
+    let budget_left = *rewind_status.budget_left.get_or_insert_with(|| {
+        budget_query
+            .iter()
+            .next()
+            .map_or_else(default_rewind_budget, |budget| budget.0)
+    });
+    let wants_to_rewind = input_views
+        .iter()
+        .any(|input_view| input_view.key(&InputBinding::Rewind).pressed());
+
+    if !wants_to_rewind || budget_left <= 0.0 || rewind_status.frames.is_empty() {
+        if rewind_status.is_rewinding {
+            rewind_status.is_rewinding = false;
+            rewind_status.unspent_time = 0.0;
+            game_clock.frozen = false;
+        }
+        let pending_grabs = some_or!(rewind_status.pending_grabs.take(); return);
+        for (grabber_entity, held) in pending_grabs {
+            let mut grab_status = some_or!(grabbers_query.get_mut(grabber_entity).ok(); continue);
+            match (&*grab_status, held) {
+                (GrabStatus::Holding { other, .. }, Some(held)) if *other == held => {
+                    continue;
+                }
+                (GrabStatus::NoGrab | GrabStatus::GrabFailed, None) => {
+                    continue;
+                }
+                (GrabStatus::Reaching { hands_entity, .. }, _) => {
+                    commands.entity(*hands_entity).despawn_recursive();
+                }
+                (
+                    GrabStatus::Holding {
+                        hands_entity,
+                        other,
+                    },
+                    _,
+                ) => {
+                    commands.entity(*hands_entity).despawn_recursive();
+                    grab_released_writer.send(GrabReleased {
+                        grabber: grabber_entity,
+                        grabbed: *other,
+                    });
+                }
+                (GrabStatus::NoGrab | GrabStatus::GrabFailed, Some(_)) => {}
+            }
+            *grab_status = if let Some(held) = held {
+                let hands_entity = spawn_hands(&mut commands, &game_assets, grabber_entity);
+                let grabbable = grabbables_query.get(held).copied().unwrap_or_default();
+                commands
+                    .entity(hands_entity)
+                    .insert(holding_joint(held, &grabbable));
+                grab_started_writer.send(GrabStarted {
+                    grabber: grabber_entity,
+                    grabbed: held,
+                });
+                GrabStatus::Holding {
+                    hands_entity,
+                    other: held,
+                }
+            } else {
+                GrabStatus::NoGrab
+            };
+        }
+        return;
+    }
+
+    rewind_status.is_rewinding = true;
+    game_clock.frozen = true;
+    let rewind_time = time.delta_seconds() * game_clock.game_speed;
+    rewind_status.budget_left = Some((budget_left - rewind_time).max(0.0));
+    rewind_status.unspent_time += rewind_time;
+
+    let mut restored_frame = None;
+    while 0.0 < rewind_status.unspent_time {
+        let frame = some_or!(rewind_status.frames.pop_back(); break);
+        rewind_status.unspent_time -= frame.delta;
+        rewind_status.frames_duration -= frame.delta;
+        restored_frame = Some(frame);
+    }
+    let frame = some_or!(restored_frame; return);
+    for (entity, transform, velocity) in frame.bodies {
+        if let Ok((mut current_transform, mut current_velocity)) = bodies_query.get_mut(entity) {
+            *current_transform = transform;
+            *current_velocity = velocity;
+        }
+    }
+    for (entity, download_progress) in frame.download_progresses {
+        if let Ok(mut current_download_progress) = download_progress_query.get_mut(entity) {
+            *current_download_progress = download_progress;
+        }
+    }
+    for (entity, transform, follower) in frame.patrols {
+        if let Ok((mut current_transform, mut current_follower)) = patrols_query.get_mut(entity) {
+            *current_transform = transform;
+            *current_follower = follower;
+        }
+    }
+    rewind_status.pending_grabs = Some(frame.grabs);
+}
@@ -2,6 +2,10 @@ use bevy::prelude::*;
 use bevy_egui::{egui, EguiContext};
 
 use crate::global_types::{DownloadProgress, WifiClient};
+use crate::rewind::RewindStatus;
+use crate::survival::SurvivalRun;
+use crate::time_trial::TimeTrialRun;
+use crate::trigger_zone::LevelTimer;
 use crate::utils::some_or;
 
 pub struct ScorePlugin;
@@ -15,6 +19,10 @@ impl Plugin for ScorePlugin {
 fn show_score(
     mut egui_context: ResMut<EguiContext>,
     player_query: Query<(&WifiClient, &DownloadProgress)>,
+    survival_run: Option<Res<SurvivalRun>>,
+    level_timer: Option<Res<LevelTimer>>,
+    time_trial_run: Option<Res<TimeTrialRun>>,
+    rewind_status: Res<RewindStatus>,
 ) {
     let (wifi_client, download_progress) = some_or!(player_query.get_single().ok(); return);
     let panel = egui::Area::new("score-area").fixed_pos([0.0, 0.0]);
@@ -23,11 +31,17 @@ fn show_score(
         ui.scope(|ui| {
             ui.style_mut().visuals.selection.bg_fill = egui::Color32::YELLOW;
             ui.add(egui::ProgressBar::new(wifi_client.signal_strength).text({
-                egui::RichText::new(format!(
-                    "Signal Strength {:.2}",
-                    wifi_client.signal_strength,
-                ))
-                .weak()
+                if wifi_client.is_jammed {
+                    egui::RichText::new("No Signal - Jammed")
+                        .strong()
+                        .color(egui::Color32::RED)
+                } else {
+                    egui::RichText::new(format!(
+                        "Signal Strength {:.2}",
+                        wifi_client.signal_strength,
+                    ))
+                    .weak()
+                }
             }));
         });
         ui.scope(|ui| match download_progress {
@@ -60,5 +74,69 @@ fn show_score(
                 );
             }
         });
+        if let Some(level_timer) = &level_timer {
+            ui.label(
+                egui::RichText::new(format!("Time Left: {:.0}", level_timer.time_left.ceil()))
+                    .strong()
+                    .color(egui::Color32::RED)
+                    .background_color(egui::Color32::BLACK),
+            );
+        }
+        if let Some(budget_left) = rewind_status.budget_left {
+            ui.label(
+                egui::RichText::new(if rewind_status.is_rewinding {
+                    format!("Rewinding... {:.1}s Left", budget_left)
+                } else {
+                    format!("Rewind: {:.1}s", budget_left)
+                })
+                .color(egui::Color32::LIGHT_BLUE)
+                .background_color(egui::Color32::BLACK),
+            );
+        }
+        if let Some(survival_run) = &survival_run {
+            ui.label(
+                egui::RichText::new(format!("Files: {}", survival_run.files_completed))
+                    .strong()
+                    .color(egui::Color32::WHITE)
+                    .background_color(egui::Color32::BLACK),
+            );
+        }
+        if let Some(time_trial_run) = &time_trial_run {
+            ui.label(
+                egui::RichText::new(format!("Time: {:.2}", time_trial_run.time))
+                    .strong()
+                    .color(egui::Color32::WHITE)
+                    .background_color(egui::Color32::BLACK),
+            );
+            for split in time_trial_run.splits.iter() {
+                let delta = time_trial_run.delta_from_personal_best(split);
+                ui.label(
+                    egui::RichText::new(if let Some(delta) = delta {
+                        format!("{}: {:.2} ({:+.2})", split.kind.label(), split.time, delta)
+                    } else {
+                        format!("{}: {:.2}", split.kind.label(), split.time)
+                    })
+                    .small()
+                    .color(match delta {
+                        Some(delta) if delta < 0.0 => egui::Color32::GREEN,
+                        Some(_) => egui::Color32::RED,
+                        None => egui::Color32::WHITE,
+                    })
+                    .background_color(egui::Color32::BLACK),
+                );
+            }
+            if let Some(personal_best) = time_trial_run
+                .personal_best
+                .as_ref()
+                .and_then(|personal_best| personal_best.last())
+            {
+                ui.label(
+                    egui::RichText::new(format!("Best: {:.2}", personal_best.time))
+                        .small()
+                        .color(egui::Color32::WHITE)
+                        .background_color(egui::Color32::BLACK),
+                );
+            }
+        }
     });
 }
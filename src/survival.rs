@@ -0,0 +1,196 @@
+use bevy::ecs::schedule::StateError;
+use bevy::prelude::*;
+use bevy_pkv::PkvStore;
+use rand::seq::IteratorRandom;
+
+use crate::door::handle_door_reached_events;
+use crate::game_clock::GameClock;
+use crate::global_types::{AppState, DownloadProgress, IsPlayer, MenuState, WifiRouter};
+use crate::loading::GameAssets;
+use crate::utils::some_or;
+use crate::zombie_spawner::{spawn_zombie_at, ZombieSpawnerStatus};
+
+pub struct SurvivalPlugin;
+
+impl Plugin for SurvivalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game)
+                .with_system(handle_survival_downloads.after(handle_door_reached_events))
+                .with_system(spawn_zombie_waves)
+                .with_system(cause_router_outages)
+        });
+        app.add_system_set(
+            SystemSet::on_enter(AppState::LoadLevel).with_system(reset_survival_run),
+        );
+        for menu_state in [MenuState::Main, MenuState::LevelSelect] {
+            app.add_system_set(
+                SystemSet::on_enter(AppState::Menu(menu_state)).with_system(end_survival_run),
+            );
+        }
+    }
+}
+
+pub const SURVIVAL_LEVEL: &str = "Survival.yol";
+pub const SURVIVAL_HIGH_SCORE_PKV_KEY: &str = "survival_high_score";
+
+const FIRST_WAVE_DELAY: f32 = 5.0;
+const WAVE_INTERVAL: f32 = 20.0;
+const OUTAGE_INTERVAL: f32 = 15.0;
+const OUTAGE_DURATION: f32 = 5.0;
+
+/// Exists as a resource only while playing in survival mode.
+pub struct SurvivalRun {
+    pub files_completed: usize,
+    wave: usize,
+    time_to_next_wave: f32,
+    time_to_next_outage: f32,
+    download_in_progress: bool,
+}
+
+impl Default for SurvivalRun {
+    fn default() -> Self {
+        Self {
+            files_completed: 0,
+            wave: 0,
+            time_to_next_wave: FIRST_WAVE_DELAY,
+            time_to_next_outage: OUTAGE_INTERVAL,
+            download_in_progress: false,
+        }
+    }
+}
+
+#[derive(Component)]
+struct RouterOutage {
+    time_left: f32,
+}
+
+pub fn survival_high_score(pkv: &PkvStore) -> usize {
+    pkv.get(SURVIVAL_HIGH_SCORE_PKV_KEY).unwrap_or(0)
+}
+
+fn reset_survival_run(survival_run: Option<ResMut<SurvivalRun>>) {
+    if let Some(mut survival_run) = survival_run {
+        *survival_run = SurvivalRun::default();
+    }
+}
+
+fn save_survival_high_score(pkv: &mut PkvStore, files_completed: usize) {
+    if survival_high_score(pkv) < files_completed {
+        if let Err(err) = pkv.set(SURVIVAL_HIGH_SCORE_PKV_KEY, &files_completed) {
+            error!("Cannot save survival high score: {}", err);
+        }
+    }
+}
+
+/// Also reached by abandoning the run from the pause menu, so the score is saved here too.
+fn end_survival_run(
+    survival_run: Option<Res<SurvivalRun>>,
+    mut pkv: ResMut<PkvStore>,
+    mut commands: Commands,
+) {
+    if let Some(survival_run) = survival_run {
+        save_survival_high_score(&mut pkv, survival_run.files_completed);
+    }
+    commands.remove_resource::<SurvivalRun>();
+}
+
+fn handle_survival_downloads(
+    survival_run: Option<ResMut<SurvivalRun>>,
+    mut player_query: Query<&mut DownloadProgress, With<IsPlayer>>,
+    mut pkv: ResMut<PkvStore>,
+    mut state: ResMut<State<AppState>>,
+) {
+    let mut survival_run = some_or!(survival_run; return);
+    for mut download_progress in player_query.iter_mut() {
+        match *download_progress {
+            DownloadProgress::Disconnected => {
+                if survival_run.download_in_progress {
+                    save_survival_high_score(&mut pkv, survival_run.files_completed);
+                    match state.set(AppState::Menu(MenuState::GameOver)) {
+                        // The level is already ending some other way on this frame.
+                        Ok(()) | Err(StateError::StateAlreadyQueued) => {}
+                        Err(err) => panic!("Cannot end the survival run: {:?}", err),
+                    }
+                    return;
+                }
+            }
+            DownloadProgress::LosingConnection { .. } | DownloadProgress::Downloading { .. } => {
+                survival_run.download_in_progress = true;
+            }
+            DownloadProgress::Completed => {
+                survival_run.files_completed += 1;
+                survival_run.download_in_progress = false;
+                *download_progress = DownloadProgress::Disconnected;
+            }
+        }
+    }
+}
+
+fn spawn_zombie_waves(
+    game_clock: Res<GameClock>,
+    survival_run: Option<ResMut<SurvivalRun>>,
+    spawners_query: Query<(&GlobalTransform, &ZombieSpawnerStatus)>,
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+) {
+    let mut survival_run = some_or!(survival_run; return);
+    survival_run.time_to_next_wave -= game_clock.delta_seconds();
+    if 0.0 < survival_run.time_to_next_wave {
+        return;
+    }
+    survival_run.wave += 1;
+    survival_run.time_to_next_wave = WAVE_INTERVAL;
+    for (spawner_transform, spawner_status) in spawners_query.iter() {
+        for _ in 0..survival_run.wave {
+            spawn_zombie_at(
+                &mut commands,
+                &game_assets,
+                spawner_status.kind,
+                spawner_transform.translation.truncate(),
+            );
+        }
+    }
+}
+
+fn cause_router_outages(
+    game_clock: Res<GameClock>,
+    survival_run: Option<ResMut<SurvivalRun>>,
+    mut routers_query: Query<(Entity, &mut WifiRouter, Option<&mut RouterOutage>)>,
+    mut commands: Commands,
+) {
+    let mut survival_run = some_or!(survival_run; return);
+    for (router_entity, mut wifi_router, outage) in routers_query.iter_mut() {
+        if let Some(mut outage) = outage {
+            outage.time_left -= game_clock.delta_seconds();
+            if outage.time_left <= 0.0 {
+                wifi_router.is_online = true;
+                commands.entity(router_entity).remove::<RouterOutage>();
+            }
+        }
+    }
+
+    survival_run.time_to_next_outage -= game_clock.delta_seconds();
+    if 0.0 < survival_run.time_to_next_outage {
+        return;
+    }
+    survival_run.time_to_next_outage = OUTAGE_INTERVAL;
+    let num_online_routers = routers_query
+        .iter()
+        .filter(|(_, wifi_router, _)| wifi_router.is_online)
+        .count();
+    // Never take down the last router - there would be nothing left to download from.
+    if num_online_routers < 2 {
+        return;
+    }
+    if let Some((router_entity, mut wifi_router, _)) = routers_query
+        .iter_mut()
+        .filter(|(_, wifi_router, _)| wifi_router.is_online)
+        .choose(&mut rand::thread_rng())
+    {
+        wifi_router.is_online = false;
+        commands.entity(router_entity).insert(RouterOutage {
+            time_left: OUTAGE_DURATION,
+        });
+    }
+}
@@ -0,0 +1,351 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_rapier2d::prelude::*;
+use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
+use bevy_yoleck::{egui, YoleckEdit, YoleckExtForApp, YoleckPopulate, YoleckTypeHandler};
+use serde::{Deserialize, Serialize};
+
+use crate::global_types::CameraInclude;
+use crate::loading::GameAssets;
+use crate::utils::some_or;
+
+pub struct TilemapPlugin;
+
+impl Plugin for TilemapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<Tilemap>::new("Tilemap")
+                .populate_with(populate)
+                .with(yoleck_vpeol_position_edit_adapter(
+                    |tilemap: &mut Tilemap| YoleckVpeolTransform2dProjection {
+                        translation: &mut tilemap.position,
+                    },
+                ))
+                .edit_with(edit)
+        });
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tilemap {
+    #[serde(default)]
+    position: Vec2,
+    #[serde(default = "default_tile_size")]
+    tile_size: f32,
+    /// Top row first, one character per tile (see `TileKind::as_char`).
+    #[serde(default)]
+    rows: Vec<String>,
+}
+
+fn default_tile_size() -> f32 {
+    0.5
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TileKind {
+    Empty,
+    Floor,
+    Wall,
+    SignalBlockingWall,
+}
+
+impl TileKind {
+    const ALL: [TileKind; 4] = [
+        TileKind::Empty,
+        TileKind::Floor,
+        TileKind::Wall,
+        TileKind::SignalBlockingWall,
+    ];
+
+    fn as_char(&self) -> char {
+        match self {
+            TileKind::Empty => '.',
+            TileKind::Floor => '_',
+            TileKind::Wall => '#',
+            TileKind::SignalBlockingWall => 'X',
+        }
+    }
+
+    fn from_char(c: char) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_char() == c)
+            .unwrap_or(TileKind::Empty)
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            TileKind::Empty => "Empty",
+            TileKind::Floor => "Floor",
+            TileKind::Wall => "Wall",
+            TileKind::SignalBlockingWall => "Signal Blocker",
+        }
+    }
+
+    fn atlas_index(&self) -> Option<usize> {
+        match self {
+            TileKind::Empty => None,
+            TileKind::Floor => Some(0),
+            TileKind::Wall => Some(1),
+            TileKind::SignalBlockingWall => Some(2),
+        }
+    }
+
+    fn is_solid(&self) -> bool {
+        matches!(self, TileKind::Wall | TileKind::SignalBlockingWall)
+    }
+}
+
+impl Tilemap {
+    fn width(&self) -> usize {
+        self.rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// `y` goes up, unlike the rows.
+    fn tile(&self, x: usize, y: usize) -> TileKind {
+        self.height()
+            .checked_sub(y + 1)
+            .and_then(|row_index| self.rows.get(row_index))
+            .and_then(|row| row.chars().nth(x))
+            .map_or(TileKind::Empty, TileKind::from_char)
+    }
+
+    fn set_tile(&mut self, x: usize, y: usize, kind: TileKind) {
+        let width = self.width();
+        let row_index = self.height() - y - 1;
+        let row = &mut self.rows[row_index];
+        let mut chars: Vec<char> = row.chars().collect();
+        chars.resize(width, TileKind::Empty.as_char());
+        chars[x] = kind.as_char();
+        *row = chars.into_iter().collect();
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        // Rows are added and removed at the top, so that the bottom-left corner stays in place
+        while self.rows.len() < height {
+            self.rows.insert(0, String::new());
+        }
+        while height < self.rows.len() {
+            self.rows.remove(0);
+        }
+        for row in self.rows.iter_mut() {
+            let mut chars: Vec<char> = row.chars().collect();
+            chars.resize(width, TileKind::Empty.as_char());
+            *row = chars.into_iter().collect();
+        }
+    }
+
+    fn size(&self) -> Vec2 {
+        self.tile_size * Vec2::new(self.width() as f32, self.height() as f32)
+    }
+
+    /// Relative to the tilemap's position, which is its center.
+    fn tile_center(&self, x: usize, y: usize) -> Vec2 {
+        self.tile_size * (Vec2::new(x as f32, y as f32) + Vec2::splat(0.5)) - 0.5 * self.size()
+    }
+
+    /// Greedily merges the solid tiles into as few rectangles as it can. The rectangles are in
+    /// tile coordinates - `(x, y, width, height)`.
+    fn solid_rectangles(&self) -> Vec<(usize, usize, usize, usize)> {
+        let (width, height) = (self.width(), self.height());
+        let mut covered = HashSet::default();
+        let mut rectangles = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                if covered.contains(&(x, y)) || !self.tile(x, y).is_solid() {
+                    continue;
+                }
+                let is_free = |x: usize, y: usize| -> bool {
+                    !covered.contains(&(x, y)) && self.tile(x, y).is_solid()
+                };
+                let rect_width = (x..width).take_while(|&x| is_free(x, y)).count();
+                let rect_height = (y..height)
+                    .take_while(|&y| (x..x + rect_width).all(|x| is_free(x, y)))
+                    .count();
+                for covered_y in y..y + rect_height {
+                    for covered_x in x..x + rect_width {
+                        covered.insert((covered_x, covered_y));
+                    }
+                }
+                rectangles.push((x, y, rect_width, rect_height));
+            }
+        }
+        rectangles
+    }
+}
+
+/// Lets WiFi signal strength be weakened by the tiles it passes through.
+#[derive(Component)]
+pub struct SignalBlockingTiles {
+    tile_size: f32,
+    /// In tile coordinates.
+    blocking_tiles: HashSet<(i32, i32)>,
+    size: Vec2,
+}
+
+/// How much of the signal is left after passing through a single signal-blocking tile.
+const SIGNAL_LEFT_PER_BLOCKING_TILE: f32 = 0.5;
+
+impl SignalBlockingTiles {
+    /// A factor (`0.0..=1.0`) for the strength of a signal going between the two points.
+    pub fn attenuation(&self, tilemap_position: Vec2, from: Vec2, to: Vec2) -> f32 {
+        let to_tile = |point: Vec2| -> (i32, i32) {
+            let local = (point - tilemap_position + 0.5 * self.size) / self.tile_size;
+            (local.x.floor() as i32, local.y.floor() as i32)
+        };
+        let num_steps = (4.0 * from.distance(to) / self.tile_size).ceil().max(1.0) as usize;
+        let mut tiles_crossed = HashSet::default();
+        for step in 0..=num_steps {
+            let tile = to_tile(from.lerp(to, step as f32 / num_steps as f32));
+            if self.blocking_tiles.contains(&tile) {
+                tiles_crossed.insert(tile);
+            }
+        }
+        SIGNAL_LEFT_PER_BLOCKING_TILE.powi(tiles_crossed.len() as i32)
+    }
+}
+
+fn populate(mut populate: YoleckPopulate<Tilemap>, game_assets: Res<GameAssets>) {
+    populate.populate(|_ctx, data, mut cmd| {
+        cmd.despawn_descendants();
+        let size = data.size();
+        // The invisible sprite is for the camera and for clicking in the editor.
+        cmd.insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::NONE,
+                custom_size: Some(size.max(Vec2::splat(data.tile_size))),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(data.position.extend(-0.5)),
+            ..Default::default()
+        });
+        cmd.insert(CameraInclude);
+
+        let mut blocking_tiles = HashSet::default();
+        cmd.with_children(|commands| {
+            for y in 0..data.height() {
+                for x in 0..data.width() {
+                    let kind = data.tile(x, y);
+                    let index = some_or!(kind.atlas_index(); continue);
+                    if kind == TileKind::SignalBlockingWall {
+                        blocking_tiles.insert((x as i32, y as i32));
+                    }
+                    commands.spawn_bundle(SpriteSheetBundle {
+                        sprite: TextureAtlasSprite {
+                            index,
+                            custom_size: Some(Vec2::splat(data.tile_size)),
+                            ..Default::default()
+                        },
+                        texture_atlas: game_assets.tiles.clone(),
+                        // Floors go below everything else
+                        transform: Transform::from_translation(
+                            data.tile_center(x, y)
+                                .extend(if kind.is_solid() { 0.5 } else { -0.1 }),
+                        ),
+                        ..Default::default()
+                    });
+                }
+            }
+        });
+        cmd.insert(SignalBlockingTiles {
+            tile_size: data.tile_size,
+            blocking_tiles,
+            size,
+        });
+
+        let shapes: Vec<(Vec2, f32, Collider)> = data
+            .solid_rectangles()
+            .into_iter()
+            .map(|(x, y, width, height)| {
+                let half_extents = 0.5 * data.tile_size * Vec2::new(width as f32, height as f32);
+                let bottom_left = data.tile_center(x, y) - Vec2::splat(0.5 * data.tile_size);
+                (
+                    bottom_left + half_extents,
+                    0.0,
+                    Collider::cuboid(half_extents.x, half_extents.y),
+                )
+            })
+            .collect();
+        if shapes.is_empty() {
+            cmd.remove::<RigidBody>();
+            cmd.remove::<Collider>();
+        } else {
+            cmd.insert(RigidBody::Fixed);
+            cmd.insert(Collider::compound(shapes));
+        }
+    });
+}
+
+fn edit(mut edit: YoleckEdit<Tilemap>, mut brush: Local<Option<TileKind>>) {
+    edit.edit(|_ctx, data, ui| {
+        ui.add(
+            egui::Slider::new(&mut data.tile_size, 0.25..=2.0)
+                .prefix("Tile Size: ")
+                .step_by(0.25),
+        );
+        let (mut width, mut height) = (data.width(), data.height());
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut width)
+                    .prefix("Width:")
+                    .clamp_range(1..=64),
+            );
+            ui.add(
+                egui::DragValue::new(&mut height)
+                    .prefix("Height:")
+                    .clamp_range(1..=64),
+            );
+        });
+        if (width, height) != (data.width(), data.height()) {
+            data.resize(width, height);
+        }
+        let brush_kind = brush.get_or_insert(TileKind::Wall);
+        ui.horizontal(|ui| {
+            ui.label("Brush:");
+            for kind in TileKind::ALL {
+                ui.selectable_value(brush_kind, kind, kind.label());
+            }
+        });
+        let brush_kind = *brush_kind;
+        ui.label("Click or drag over tiles to paint:");
+        egui::Grid::new("tilemap-grid")
+            .spacing([1.0, 1.0])
+            .show(ui, |ui| {
+                for y in (0..data.height()).rev() {
+                    for x in 0..data.width() {
+                        let kind = data.tile(x, y);
+                        let color = match kind {
+                            TileKind::Empty => egui::Color32::from_gray(20),
+                            TileKind::Floor => egui::Color32::from_rgb(200, 196, 188),
+                            TileKind::Wall => egui::Color32::from_gray(70),
+                            TileKind::SignalBlockingWall => egui::Color32::from_rgb(60, 70, 100),
+                        };
+                        let (rect, response) = ui.allocate_exact_size(
+                            egui::Vec2::splat(12.0),
+                            egui::Sense::click_and_drag(),
+                        );
+                        ui.painter().rect_filled(rect, 0.0, color);
+                        let is_painted_over = response.clicked()
+                            || (ui.input().pointer.primary_down()
+                                && ui
+                                    .input()
+                                    .pointer
+                                    .interact_pos()
+                                    .map_or(false, |pos| rect.contains(pos)));
+                        if is_painted_over && kind != brush_kind {
+                            data.set_tile(x, y, brush_kind);
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+}
@@ -0,0 +1,187 @@
+use bevy::ecs::schedule::StateError;
+use bevy::prelude::*;
+use bevy_pkv::PkvStore;
+use ezinput::prelude::{InputView, PressStateExt};
+use serde::{Deserialize, Serialize};
+
+use crate::door::handle_door_reached_events;
+use crate::game_clock::GameClock;
+use crate::global_types::{
+    AppState, DownloadCompleted, DownloadStarted, InputBinding, LevelCompleted, LevelProgress,
+    MenuState,
+};
+use crate::utils::some_or;
+
+pub struct TimeTrialPlugin;
+
+impl Plugin for TimeTrialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game)
+                .with_system(advance_time_trial_clock)
+                .with_system(record_splits.after(advance_time_trial_clock))
+                .with_system(
+                    finish_time_trial
+                        .after(record_splits)
+                        .after(handle_door_reached_events),
+                )
+                .with_system(quick_restart.after(handle_door_reached_events))
+        });
+        app.add_system_set(
+            SystemSet::on_enter(AppState::LoadLevel).with_system(reset_time_trial_run),
+        );
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Menu(MenuState::Main)).with_system(end_time_trial_run),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SplitKind {
+    DownloadStarted,
+    DownloadCompleted,
+    DoorReached,
+}
+
+impl SplitKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SplitKind::DownloadStarted => "Download Started",
+            SplitKind::DownloadCompleted => "Download Completed",
+            SplitKind::DoorReached => "Door Reached",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Split {
+    pub kind: SplitKind,
+    pub time: f32,
+}
+
+/// Exists as a resource only while playing in time-trial mode.
+#[derive(Default)]
+pub struct TimeTrialRun {
+    pub time: f32,
+    pub splits: Vec<Split>,
+    pub personal_best: Option<Vec<Split>>,
+}
+
+impl TimeTrialRun {
+    /// How much slower (positive) or faster (negative) the split is compared to the personal best.
+    pub fn delta_from_personal_best(&self, split: &Split) -> Option<f32> {
+        let personal_best = self.personal_best.as_ref()?;
+        let best_split = personal_best.iter().find(|best| best.kind == split.kind)?;
+        Some(split.time - best_split.time)
+    }
+
+    fn record_split(&mut self, kind: SplitKind) {
+        if self.splits.iter().any(|split| split.kind == kind) {
+            return;
+        }
+        self.splits.push(Split {
+            kind,
+            time: self.time,
+        });
+    }
+}
+
+fn personal_best_pkv_key(level: &str) -> String {
+    format!("time_trial:{}", level)
+}
+
+fn load_personal_best(pkv: &PkvStore, level: &str) -> Option<Vec<Split>> {
+    pkv.get(&personal_best_pkv_key(level)).ok()
+}
+
+fn reset_time_trial_run(
+    time_trial_run: Option<ResMut<TimeTrialRun>>,
+    level_progress: Res<LevelProgress>,
+    pkv: Res<PkvStore>,
+) {
+    let mut time_trial_run = some_or!(time_trial_run; return);
+    *time_trial_run = TimeTrialRun {
+        personal_best: level_progress
+            .current_level
+            .as_ref()
+            .and_then(|level| load_personal_best(&pkv, level)),
+        ..Default::default()
+    };
+}
+
+fn end_time_trial_run(mut commands: Commands) {
+    commands.remove_resource::<TimeTrialRun>();
+}
+
+fn advance_time_trial_clock(
+    game_clock: Res<GameClock>,
+    time_trial_run: Option<ResMut<TimeTrialRun>>,
+) {
+    let mut time_trial_run = some_or!(time_trial_run; return);
+    time_trial_run.time += game_clock.delta_seconds();
+}
+
+fn record_splits(
+    time_trial_run: Option<ResMut<TimeTrialRun>>,
+    mut download_started_reader: EventReader<DownloadStarted>,
+    mut download_completed_reader: EventReader<DownloadCompleted>,
+) {
+    let mut time_trial_run = some_or!(time_trial_run; return);
+    if download_started_reader.iter().next().is_some() {
+        time_trial_run.record_split(SplitKind::DownloadStarted);
+    }
+    if download_completed_reader.iter().next().is_some() {
+        time_trial_run.record_split(SplitKind::DownloadCompleted);
+    }
+}
+
+fn finish_time_trial(
+    time_trial_run: Option<ResMut<TimeTrialRun>>,
+    mut level_completed_reader: EventReader<LevelCompleted>,
+    level_progress: Res<LevelProgress>,
+    mut pkv: ResMut<PkvStore>,
+) {
+    let mut time_trial_run = some_or!(time_trial_run; return);
+    if level_completed_reader.iter().next().is_none() {
+        return;
+    }
+    time_trial_run.record_split(SplitKind::DoorReached);
+    let level = some_or!(
+        level_progress.current_level.as_ref();
+        return // level completed inside editor
+    );
+    let is_new_personal_best = time_trial_run
+        .splits
+        .last()
+        .and_then(|split| time_trial_run.delta_from_personal_best(split))
+        .map_or(true, |delta| delta < 0.0);
+    if is_new_personal_best {
+        if let Err(err) = pkv.set(&personal_best_pkv_key(level), &time_trial_run.splits) {
+            error!("Cannot save personal best for {}: {}", level, err);
+        }
+    }
+}
+
+fn quick_restart(
+    time_trial_run: Option<Res<TimeTrialRun>>,
+    input_views: Query<&InputView<InputBinding>>,
+    mut level_progress: ResMut<LevelProgress>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if time_trial_run.is_none() {
+        return;
+    }
+    if input_views
+        .iter()
+        .any(|input_view| input_view.key(&InputBinding::QuickRestart).just_pressed())
+    {
+        match state.set(AppState::LoadLevel) {
+            Ok(()) => {
+                level_progress.skip_level_splash = true;
+            }
+            // The level is already ending some other way on this frame.
+            Err(StateError::StateAlreadyQueued) => {}
+            Err(err) => panic!("Cannot quick restart: {:?}", err),
+        }
+    }
+}
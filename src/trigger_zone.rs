@@ -0,0 +1,341 @@
+use bevy::ecs::schedule::StateError;
+use bevy::prelude::*;
+use bevy_rapier2d::pipeline::CollisionEvent;
+use bevy_rapier2d::prelude::{ActiveEvents, Collider, Sensor};
+use bevy_rapier2d::rapier::prelude::CollisionEventFlags;
+use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
+use bevy_yoleck::{
+    egui, YoleckEdit, YoleckExtForApp, YoleckManaged, YoleckPopulate, YoleckTypeHandler,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::conveyor::ConveyorStatus;
+use crate::door::handle_door_reached_events;
+use crate::game_clock::GameClock;
+use crate::global_types::{AppState, DoorStatus, IsPlayer, MenuState, WifiRouter};
+use crate::loading::GameAssets;
+use crate::utils::entities_ordered_by_type;
+use crate::zombie_spawner::{spawn_zombie_at, ZombieSpawnerStatus};
+
+pub struct TriggerZonePlugin;
+
+impl Plugin for TriggerZonePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<TriggerZone>::new("TriggerZone")
+                .populate_with(populate)
+                .with(yoleck_vpeol_position_edit_adapter(
+                    |trigger_zone: &mut TriggerZone| YoleckVpeolTransform2dProjection {
+                        translation: &mut trigger_zone.position,
+                    },
+                ))
+                .edit_with(edit)
+        });
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game)
+                .with_system(handle_trigger_zone_events)
+                .with_system(count_down_level_timer.after(handle_door_reached_events))
+        });
+        app.add_system_set(
+            SystemSet::on_enter(AppState::LoadLevel)
+                .with_system(|mut commands: Commands| commands.remove_resource::<LevelTimer>()),
+        );
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct TriggerZone {
+    #[serde(default)]
+    position: Vec2,
+    #[serde(default = "default_size")]
+    size: Vec2,
+    #[serde(default = "default_once")]
+    once: bool,
+    #[serde(default)]
+    actions: Vec<TriggerAction>,
+}
+
+fn default_size() -> Vec2 {
+    Vec2::new(1.0, 1.0)
+}
+
+fn default_once() -> bool {
+    true
+}
+
+/// Actions refer to other entities by their Yoleck name.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum TriggerAction {
+    Show { target: String },
+    Hide { target: String },
+    ToggleRouter { target: String },
+    ToggleConveyor { target: String },
+    OpenDoor { target: String },
+    SpawnZombies { target: String, count: usize },
+    StartTimer { seconds: f32 },
+}
+
+impl TriggerAction {
+    fn label(&self) -> &'static str {
+        match self {
+            TriggerAction::Show { .. } => "Show",
+            TriggerAction::Hide { .. } => "Hide",
+            TriggerAction::ToggleRouter { .. } => "Toggle Router",
+            TriggerAction::ToggleConveyor { .. } => "Toggle Conveyor",
+            TriggerAction::OpenDoor { .. } => "Open Door",
+            TriggerAction::SpawnZombies { .. } => "Spawn Zombies",
+            TriggerAction::StartTimer { .. } => "Start Timer",
+        }
+    }
+
+    fn all_defaults() -> [TriggerAction; 7] {
+        [
+            TriggerAction::Show {
+                target: String::new(),
+            },
+            TriggerAction::Hide {
+                target: String::new(),
+            },
+            TriggerAction::ToggleRouter {
+                target: String::new(),
+            },
+            TriggerAction::ToggleConveyor {
+                target: String::new(),
+            },
+            TriggerAction::OpenDoor {
+                target: String::new(),
+            },
+            TriggerAction::SpawnZombies {
+                target: String::new(),
+                count: 3,
+            },
+            TriggerAction::StartTimer { seconds: 30.0 },
+        ]
+    }
+}
+
+#[derive(Component)]
+struct TriggerZoneStatus {
+    once: bool,
+    actions: Vec<TriggerAction>,
+    fired: bool,
+}
+
+/// A countdown started by a trigger. The level is lost when it runs out.
+pub struct LevelTimer {
+    pub time_left: f32,
+}
+
+fn populate(mut populate: YoleckPopulate<TriggerZone>) {
+    populate.populate(|ctx, data, mut cmd| {
+        cmd.insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: if ctx.is_in_editor() {
+                    Color::rgba(1.0, 0.8, 0.0, 0.2)
+                } else {
+                    Color::NONE
+                },
+                custom_size: Some(data.size),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(data.position.extend(-0.2)),
+            ..Default::default()
+        });
+        cmd.insert(TriggerZoneStatus {
+            once: data.once,
+            actions: data.actions.clone(),
+            fired: false,
+        });
+        cmd.insert(Collider::cuboid(0.5 * data.size.x, 0.5 * data.size.y));
+        cmd.insert(Sensor(true));
+        cmd.insert(ActiveEvents::COLLISION_EVENTS);
+    });
+}
+
+fn edit(mut edit: YoleckEdit<TriggerZone>) {
+    edit.edit(|_ctx, data, ui| {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut data.size.x)
+                    .prefix("Width:")
+                    .speed(0.05),
+            );
+            ui.add(
+                egui::DragValue::new(&mut data.size.y)
+                    .prefix("Height:")
+                    .speed(0.05),
+            );
+        });
+        ui.checkbox(&mut data.once, "Only Once");
+        let mut to_remove = None;
+        for (index, action) in data.actions.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source(("trigger-action", index))
+                    .selected_text(action.label())
+                    .show_ui(ui, |ui| {
+                        for option in TriggerAction::all_defaults() {
+                            if ui
+                                .selectable_label(option.label() == action.label(), option.label())
+                                .clicked()
+                                && option.label() != action.label()
+                            {
+                                *action = option;
+                            }
+                        }
+                    });
+                match action {
+                    TriggerAction::Show { target }
+                    | TriggerAction::Hide { target }
+                    | TriggerAction::ToggleRouter { target }
+                    | TriggerAction::ToggleConveyor { target }
+                    | TriggerAction::OpenDoor { target } => {
+                        ui.text_edit_singleline(target);
+                    }
+                    TriggerAction::SpawnZombies { target, count } => {
+                        ui.text_edit_singleline(target);
+                        ui.add(egui::DragValue::new(count).clamp_range(1..=20));
+                    }
+                    TriggerAction::StartTimer { seconds } => {
+                        ui.add(
+                            egui::DragValue::new(seconds)
+                                .suffix("s")
+                                .clamp_range(1.0..=600.0),
+                        );
+                    }
+                }
+                if ui.small_button("X").clicked() {
+                    to_remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = to_remove {
+            data.actions.remove(index);
+        }
+        if ui.button("Add Action").clicked() {
+            data.actions.push(TriggerAction::Show {
+                target: String::new(),
+            });
+        }
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_trigger_zone_events(
+    mut reader: EventReader<CollisionEvent>,
+    player_query: Query<(), With<IsPlayer>>,
+    mut trigger_zones_query: Query<&mut TriggerZoneStatus>,
+    named_entities_query: Query<(Entity, &YoleckManaged)>,
+    mut visibility_query: Query<&mut Visibility>,
+    mut routers_query: Query<&mut WifiRouter>,
+    mut conveyors_query: Query<&mut ConveyorStatus>,
+    mut doors_query: Query<&mut DoorStatus>,
+    spawners_query: Query<(&GlobalTransform, &ZombieSpawnerStatus)>,
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+) {
+    for event in reader.iter() {
+        let [_player_entity, trigger_zone_entity] = match event {
+            CollisionEvent::Started(entity1, entity2, flags) => {
+                if flags.contains(CollisionEventFlags::SENSOR) {
+                    if let Some(entities) = entities_ordered_by_type!(
+                        [*entity1, *entity2],
+                        player_query,
+                        trigger_zones_query,
+                    ) {
+                        entities
+                    } else {
+                        continue;
+                    }
+                } else {
+                    continue;
+                }
+            }
+            CollisionEvent::Stopped(_, _, _) => {
+                continue;
+            }
+        };
+        let mut trigger_zone = trigger_zones_query.get_mut(trigger_zone_entity).unwrap();
+        if trigger_zone.once && trigger_zone.fired {
+            continue;
+        }
+        trigger_zone.fired = true;
+        let targets = |name: &str| -> Vec<Entity> {
+            named_entities_query
+                .iter()
+                .filter(|(_, yoleck_managed)| yoleck_managed.name == name)
+                .map(|(entity, _)| entity)
+                .collect()
+        };
+        for action in trigger_zone.actions.iter() {
+            match action {
+                TriggerAction::Show { target } | TriggerAction::Hide { target } => {
+                    let is_visible = matches!(action, TriggerAction::Show { .. });
+                    for entity in targets(target) {
+                        if let Ok(mut visibility) = visibility_query.get_mut(entity) {
+                            visibility.is_visible = is_visible;
+                        }
+                    }
+                }
+                TriggerAction::ToggleRouter { target } => {
+                    for entity in targets(target) {
+                        if let Ok(mut wifi_router) = routers_query.get_mut(entity) {
+                            wifi_router.is_online = !wifi_router.is_online;
+                        }
+                    }
+                }
+                TriggerAction::ToggleConveyor { target } => {
+                    for entity in targets(target) {
+                        if let Ok(mut conveyor_status) = conveyors_query.get_mut(entity) {
+                            conveyor_status.is_on = !conveyor_status.is_on;
+                        }
+                    }
+                }
+                TriggerAction::OpenDoor { target } => {
+                    for entity in targets(target) {
+                        if let Ok(mut door_status) = doors_query.get_mut(entity) {
+                            door_status.is_forced_open = true;
+                        }
+                    }
+                }
+                TriggerAction::SpawnZombies { target, count } => {
+                    for entity in targets(target) {
+                        if let Ok((spawner_transform, spawner_status)) = spawners_query.get(entity)
+                        {
+                            for _ in 0..*count {
+                                spawn_zombie_at(
+                                    &mut commands,
+                                    &game_assets,
+                                    spawner_status.kind,
+                                    spawner_transform.translation.truncate(),
+                                );
+                            }
+                        }
+                    }
+                }
+                TriggerAction::StartTimer { seconds } => {
+                    commands.insert_resource(LevelTimer {
+                        time_left: *seconds,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn count_down_level_timer(
+    game_clock: Res<GameClock>,
+    level_timer: Option<ResMut<LevelTimer>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if let Some(mut level_timer) = level_timer {
+        level_timer.time_left -= game_clock.delta_seconds();
+        if level_timer.time_left <= 0.0 {
+            level_timer.time_left = 0.0;
+            match state.set(AppState::Menu(MenuState::GameOver)) {
+                // The player reached the door on the very same frame, and then they should win.
+                Ok(()) | Err(StateError::StateAlreadyQueued) => {}
+                Err(err) => panic!("Cannot end the level when the timer runs out: {:?}", err),
+            }
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use bevy::math::Affine2;
 use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::sprite::MaterialMesh2dBundle;
 use bevy_egui::egui;
 use bevy_rapier2d::prelude::*;
 use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
@@ -28,62 +30,398 @@ impl Plugin for WallPlugin {
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Wall {
     #[serde(default)]
-    position: Vec2,
+    pub(crate) position: Vec2,
     #[serde(default = "default_size")]
-    size: Vec2,
+    pub(crate) size: Vec2,
     #[serde(default)]
-    rotation: f32,
+    pub(crate) rotation: f32,
+    #[serde(default)]
+    pub(crate) shape: WallShape,
 }
 
 fn default_size() -> Vec2 {
     Vec2::new(1.0, 1.0)
 }
 
-fn populate(mut populate: YoleckPopulate<Wall>) {
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum WallShape {
+    /// Uses the wall's `size`.
+    Rectangle,
+    Circle {
+        radius: f32,
+    },
+    /// Vertical before rotation.
+    Capsule {
+        half_length: f32,
+        radius: f32,
+    },
+    /// Relative to the wall's position. Concave polygons are replaced by their convex hull.
+    Polygon {
+        vertices: Vec<Vec2>,
+    },
+}
+
+impl Default for WallShape {
+    fn default() -> Self {
+        Self::Rectangle
+    }
+}
+
+const CURVE_SEGMENTS: usize = 32;
+
+impl WallShape {
+    fn outline(&self, size: Vec2) -> Vec<Vec2> {
+        match self {
+            WallShape::Rectangle => {
+                let half_size = 0.5 * size;
+                vec![
+                    Vec2::new(-half_size.x, -half_size.y),
+                    Vec2::new(half_size.x, -half_size.y),
+                    Vec2::new(half_size.x, half_size.y),
+                    Vec2::new(-half_size.x, half_size.y),
+                ]
+            }
+            WallShape::Circle { radius } => arc(Vec2::ZERO, *radius, 0.0, CURVE_SEGMENTS),
+            WallShape::Capsule {
+                half_length,
+                radius,
+            } => {
+                use std::f32::consts::PI;
+                let mut outline = arc(
+                    Vec2::new(0.0, -half_length),
+                    *radius,
+                    PI,
+                    CURVE_SEGMENTS / 2,
+                );
+                outline.extend(arc(
+                    Vec2::new(0.0, *half_length),
+                    *radius,
+                    0.0,
+                    CURVE_SEGMENTS / 2,
+                ));
+                outline
+            }
+            WallShape::Polygon { vertices } => convex_hull(vertices),
+        }
+    }
+
+    fn collider(&self, size: Vec2) -> Collider {
+        match self {
+            WallShape::Rectangle => Collider::cuboid(0.5 * size.x, 0.5 * size.y),
+            WallShape::Circle { radius } => Collider::ball(*radius),
+            WallShape::Capsule {
+                half_length,
+                radius,
+            } => Collider::capsule_y(*half_length, *radius),
+            WallShape::Polygon { vertices } => {
+                Collider::convex_hull(vertices).unwrap_or_else(|| Collider::ball(0.1))
+            }
+        }
+    }
+}
+
+/// Counter-clockwise from `start_angle`, with segments the size of a full circle's. A full circle
+/// (`num_segments == CURVE_SEGMENTS`) does not repeat its first point.
+fn arc(center: Vec2, radius: f32, start_angle: f32, num_segments: usize) -> Vec<Vec2> {
+    let full_circle = num_segments == CURVE_SEGMENTS;
+    let num_points = if full_circle {
+        num_segments
+    } else {
+        num_segments + 1
+    };
+    (0..num_points)
+        .map(|i| {
+            let angle = start_angle + std::f32::consts::TAU * i as f32 / CURVE_SEGMENTS as f32;
+            center + radius * Vec2::new(angle.cos(), angle.sin())
+        })
+        .collect()
+}
+
+/// Counter-clockwise, using the monotone chain algorithm.
+fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut points = points.to_vec();
+    // `FloatOrd` so that a NaN in a hand-edited level doesn't crash the game.
+    points.sort_by_key(|point| (float_ord::FloatOrd(point.x), float_ord::FloatOrd(point.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: Vec2, a: Vec2, b: Vec2| (a - o).perp_dot(b - o);
+    let mut hull: Vec<Vec2> = Vec::with_capacity(2 * points.len());
+    for pass in [points.clone(), points.iter().rev().copied().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while start + 2 <= hull.len()
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+/// A triangle fan - only correct for convex outlines.
+pub fn convex_polygon_mesh(outline: &[Vec2]) -> Mesh {
+    let positions: Vec<[f32; 3]> = outline.iter().map(|v| [v.x, v.y, 0.0]).collect();
+    let normals = vec![[0.0, 0.0, 1.0]; outline.len()];
+    let uvs = vec![[0.0, 0.0]; outline.len()];
+    let indices = (1..outline.len().saturating_sub(1) as u32)
+        .flat_map(|i| [0, i, i + 1])
+        .collect();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+fn populate(
+    mut populate: YoleckPopulate<Wall>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
     populate.populate(|_ctx, data, mut cmd| {
-        cmd.insert_bundle(SpriteBundle {
-            sprite: Sprite {
-                color: Color::DARK_GRAY,
-                custom_size: Some(data.size),
+        cmd.despawn_descendants();
+        let transform = Transform::from_translation(data.position.extend(0.0))
+            .with_rotation(Quat::from_rotation_z(data.rotation));
+        if let WallShape::Rectangle = data.shape {
+            cmd.insert_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::DARK_GRAY,
+                    custom_size: Some(data.size),
+                    ..Default::default()
+                },
+                transform,
                 ..Default::default()
-            },
-            transform: Transform::from_translation(data.position.extend(0.0))
-                .with_rotation(Quat::from_rotation_z(data.rotation)),
-            ..Default::default()
-        });
+            });
+        } else {
+            let outline = data.shape.outline(data.size);
+            let extent = outline
+                .iter()
+                .fold(Vec2::ZERO, |extent, vertex| extent.max(vertex.abs()));
+            // The invisible sprite is for the camera and for clicking in the editor.
+            cmd.insert_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::NONE,
+                    custom_size: Some(2.0 * extent),
+                    ..Default::default()
+                },
+                transform,
+                ..Default::default()
+            });
+            let mesh = meshes.add(convex_polygon_mesh(&outline));
+            let material = materials.add(ColorMaterial::from(Color::DARK_GRAY));
+            cmd.with_children(|commands| {
+                commands.spawn_bundle(MaterialMesh2dBundle {
+                    mesh: mesh.into(),
+                    material,
+                    ..Default::default()
+                });
+            });
+        }
         cmd.insert(CameraInclude);
         cmd.insert(RigidBody::Fixed);
-        cmd.insert(Collider::cuboid(0.5 * data.size.x, 0.5 * data.size.y));
+        cmd.insert(data.shape.collider(data.size));
     });
 }
 
 fn edit(mut edit: YoleckEdit<Wall>) {
     edit.edit(|_ctx, data, ui| {
         use std::f32::consts::{FRAC_PI_8, PI};
+        ui.horizontal(|ui| {
+            ui.label("Shape:");
+            for (label, is_selected, shape) in [
+                (
+                    "Rectangle",
+                    matches!(data.shape, WallShape::Rectangle),
+                    WallShape::Rectangle,
+                ),
+                (
+                    "Circle",
+                    matches!(data.shape, WallShape::Circle { .. }),
+                    WallShape::Circle {
+                        radius: 0.5 * data.size.min_element(),
+                    },
+                ),
+                (
+                    "Capsule",
+                    matches!(data.shape, WallShape::Capsule { .. }),
+                    WallShape::Capsule {
+                        half_length: 0.5 * data.size.y,
+                        radius: 0.5 * data.size.x,
+                    },
+                ),
+                (
+                    "Polygon",
+                    matches!(data.shape, WallShape::Polygon { .. }),
+                    WallShape::Polygon {
+                        vertices: data.shape.outline(data.size),
+                    },
+                ),
+            ] {
+                if ui.selectable_label(is_selected, label).clicked() && !is_selected {
+                    data.shape = shape;
+                }
+            }
+        });
         let orig_rotation = data.rotation;
         ui.add({
             egui::Slider::new(&mut data.rotation, PI..=-PI)
                 .prefix("Angle: ")
                 .step_by(FRAC_PI_8 as f64)
         });
-        let orig_size = data.size;
-        ui.horizontal(|ui| {
-            ui.add(
-                egui::DragValue::new(&mut data.size.x)
-                    .prefix("Width:")
-                    .speed(0.05),
-            );
-            ui.add(
-                egui::DragValue::new(&mut data.size.y)
-                    .prefix("Height:")
-                    .speed(0.05),
-            );
-        });
-        if (orig_size, orig_rotation) != (data.size, data.rotation) {
-            let top_left = data.position
-                - 0.5 * Affine2::from_angle(orig_rotation).transform_vector2(orig_size);
-            data.position =
-                top_left + 0.5 * Affine2::from_angle(data.rotation).transform_vector2(data.size);
+        match &mut data.shape {
+            WallShape::Rectangle => {
+                let orig_size = data.size;
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(&mut data.size.x)
+                            .prefix("Width:")
+                            .speed(0.05),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut data.size.y)
+                            .prefix("Height:")
+                            .speed(0.05),
+                    );
+                });
+                if (orig_size, orig_rotation) != (data.size, data.rotation) {
+                    let top_left = data.position
+                        - 0.5 * Affine2::from_angle(orig_rotation).transform_vector2(orig_size);
+                    data.position = top_left
+                        + 0.5 * Affine2::from_angle(data.rotation).transform_vector2(data.size);
+                }
+            }
+            WallShape::Circle { radius } => {
+                ui.add(
+                    egui::DragValue::new(radius)
+                        .prefix("Radius:")
+                        .speed(0.05)
+                        .clamp_range(0.1..=20.0),
+                );
+            }
+            WallShape::Capsule {
+                half_length,
+                radius,
+            } => {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(half_length)
+                            .prefix("Half Length:")
+                            .speed(0.05)
+                            .clamp_range(0.0..=20.0),
+                    );
+                    ui.add(
+                        egui::DragValue::new(radius)
+                            .prefix("Radius:")
+                            .speed(0.05)
+                            .clamp_range(0.1..=20.0),
+                    );
+                });
+            }
+            WallShape::Polygon { vertices } => {
+                ui.label("Vertices (relative to the wall's position):");
+                let mut to_remove = None;
+                for (index, vertex) in vertices.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut vertex.x).prefix("X:").speed(0.05));
+                        ui.add(egui::DragValue::new(&mut vertex.y).prefix("Y:").speed(0.05));
+                        if ui.small_button("X").clicked() {
+                            to_remove = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = to_remove {
+                    if 3 < vertices.len() {
+                        vertices.remove(index);
+                    }
+                }
+                if ui.button("Add Vertex").clicked() {
+                    // Between the last and the first, pushed a little outwards so that the
+                    // polygon stays convex and the convex hull doesn't drop the new vertex.
+                    let new_vertex = match (vertices.first(), vertices.last()) {
+                        (Some(first), Some(last)) => {
+                            let midpoint = 0.5 * (*first + *last);
+                            let center = vertices.iter().fold(Vec2::ZERO, |sum, v| sum + *v)
+                                / vertices.len() as f32;
+                            let mut normal = (*first - *last).perp();
+                            if normal.dot(midpoint - center) < 0.0 {
+                                normal = -normal;
+                            }
+                            midpoint + 0.1 * normal
+                        }
+                        _ => Vec2::ZERO,
+                    };
+                    vertices.push(new_vertex);
+                }
+                if convex_hull(vertices).len() < vertices.len() {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        "Polygon is not convex - its convex hull will be used",
+                    );
+                }
+            }
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convex_hull_drops_interior_points() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.5, 0.5),
+            Vec2::new(0.0, 1.0),
+            Vec2::new(1.0, 0.0),
+        ];
+        assert_eq!(
+            convex_hull(&points),
+            vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(1.0, 0.0),
+                Vec2::new(1.0, 1.0),
+                Vec2::new(0.0, 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn convex_hull_drops_collinear_points() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(0.0, 2.0),
+        ];
+        assert_eq!(
+            convex_hull(&points),
+            vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(2.0, 0.0),
+                Vec2::new(2.0, 2.0),
+                Vec2::new(0.0, 2.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn convex_hull_does_not_panic_on_nan() {
+        let points = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(f32::NAN, 1.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, f32::NAN),
+            Vec2::new(0.0, 1.0),
+        ];
+        convex_hull(&points);
+    }
+}
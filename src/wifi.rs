@@ -1,10 +1,22 @@
 use bevy::prelude::*;
+use bevy::sprite::MaterialMesh2dBundle;
+use bevy_rapier2d::prelude::*;
 use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
 use bevy_yoleck::{egui, YoleckEdit, YoleckExtForApp, YoleckPopulate, YoleckTypeHandler};
 use serde::{Deserialize, Serialize};
 
-use crate::global_types::{AppState, CameraInclude, DownloadProgress, WifiClient, WifiRouter};
+use crate::game_clock::GameClock;
+use crate::global_types::{
+    AccessPointChanged, AppState, CameraInclude, ConnectionLost, DownloadCompleted,
+    DownloadProgress, DownloadStarted, GrabMode, Grabbable, Reconnected, SignalAttenuator,
+    SignalBeam, WifiClient, WifiRouter,
+};
+use crate::jammer::{FaradayCage, SignalJammer};
 use crate::loading::GameAssets;
+use crate::patrol_path::{PatrolPath, PatrolPathFollower};
+use crate::tilemap::SignalBlockingTiles;
+use crate::utils::some_or;
+use crate::wall::convex_polygon_mesh;
 
 pub struct WifiPlugin;
 
@@ -20,10 +32,17 @@ impl Plugin for WifiPlugin {
                 }))
                 .edit_with(edit)
         });
+        app.add_event::<DownloadStarted>();
+        app.add_event::<ConnectionLost>();
+        app.add_event::<Reconnected>();
+        app.add_event::<DownloadCompleted>();
+        app.add_event::<AccessPointChanged>();
         app.add_system_set({
             SystemSet::on_update(AppState::Game)
                 .with_system(update_access_points)
                 .with_system(update_download_progress)
+                .with_system(update_router_sprites)
+                .with_system(update_beam_cones)
         });
     }
 }
@@ -31,15 +50,43 @@ impl Plugin for WifiPlugin {
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Wifi {
     #[serde(default)]
-    position: Vec2,
+    pub(crate) position: Vec2,
     #[serde(default)]
-    full_strengh_radius: f32,
+    pub(crate) full_strengh_radius: f32,
+    #[serde(default)]
+    pub(crate) path: PatrolPath,
+    #[serde(default)]
+    pub(crate) beam: Option<SignalBeam>,
+    /// Can be picked up and carried around by the player. Portable routers don't follow paths.
+    #[serde(default)]
+    pub(crate) is_portable: bool,
 }
 
-fn populate(mut populate: YoleckPopulate<Wifi>, game_assets: Res<GameAssets>) {
-    populate.populate(|_ctx, data, mut cmd| {
+/// Marks the child that shows which way a directional router is transmitting.
+#[derive(Component)]
+struct BeamCone;
+
+/// How far the beam cone reaches. Only affects the visuals.
+const BEAM_CONE_LENGTH: f32 = 3.0;
+
+/// The signal does not drop all at once at the edge of the beam.
+const BEAM_EDGE_SOFTNESS: f32 = std::f32::consts::FRAC_PI_4;
+
+/// How much of the signal is left well outside the beam.
+const SIGNAL_LEFT_OUTSIDE_BEAM: f32 = 0.2;
+
+fn populate(
+    mut populate: YoleckPopulate<Wifi>,
+    game_assets: Res<GameAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    populate.populate(|ctx, data, mut cmd| {
+        cmd.despawn_descendants();
         cmd.insert(WifiRouter {
             full_strengh_radius: data.full_strengh_radius,
+            is_online: true,
+            beam: data.beam,
         });
         cmd.insert_bundle(SpriteBundle {
             sprite: Sprite {
@@ -54,6 +101,56 @@ fn populate(mut populate: YoleckPopulate<Wifi>, game_assets: Res<GameAssets>) {
             Transform::from_translation(data.position.extend(1.0)),
         ));
         cmd.insert(CameraInclude);
+        if let Some(beam) = data.beam {
+            let half_width = 0.5 * beam.width;
+            let num_segments = 16;
+            let outline: Vec<Vec2> = std::iter::once(Vec2::ZERO)
+                .chain((0..=num_segments).map(|i| {
+                    let angle = -half_width + beam.width * i as f32 / num_segments as f32;
+                    BEAM_CONE_LENGTH * Vec2::new(angle.cos(), angle.sin())
+                }))
+                .collect();
+            let mesh = meshes.add(convex_polygon_mesh(&outline));
+            let material = materials.add(ColorMaterial::from(Color::rgba(1.0, 1.0, 0.4, 0.15)));
+            cmd.with_children(|commands| {
+                commands
+                    .spawn_bundle(MaterialMesh2dBundle {
+                        mesh: mesh.into(),
+                        material,
+                        // Below the router, and also below most of the level
+                        transform: Transform::from_xyz(0.0, 0.0, -1.2)
+                            .with_rotation(Quat::from_rotation_z(beam.direction)),
+                        ..Default::default()
+                    })
+                    .insert(BeamCone);
+            });
+        }
+        if data.is_portable {
+            cmd.insert(RigidBody::Dynamic);
+            cmd.insert(Damping {
+                linear_damping: 2.0,
+                angular_damping: 2.0,
+            });
+            cmd.insert(Collider::ball(0.3));
+            cmd.insert(ColliderMassProperties::Density(5.0));
+            cmd.insert(Velocity::default());
+            cmd.insert(Grabbable {
+                mode: GrabMode::Lift,
+                ..Default::default()
+            });
+            cmd.remove::<PatrolPathFollower>();
+        } else {
+            cmd.remove_bundle::<(
+                RigidBody,
+                Damping,
+                Collider,
+                ColliderMassProperties,
+                Velocity,
+            )>();
+            cmd.remove::<Grabbable>();
+            data.path
+                .populate(&mut cmd, data.position, ctx.is_in_editor());
+        }
     });
 }
 
@@ -63,45 +160,194 @@ fn edit(mut edit: YoleckEdit<Wifi>) {
             egui::Slider::new(&mut data.full_strengh_radius, 0.0..=4.0)
                 .prefix("Full Strength Radius: ")
         });
+        let mut is_directional = data.beam.is_some();
+        ui.checkbox(&mut is_directional, "Directional");
+        if is_directional != data.beam.is_some() {
+            data.beam = is_directional.then(|| SignalBeam {
+                direction: 0.0,
+                width: std::f32::consts::FRAC_PI_2,
+            });
+        }
+        if let Some(beam) = data.beam.as_mut() {
+            use std::f32::consts::{FRAC_PI_8, PI};
+            ui.add({
+                egui::Slider::new(&mut beam.direction, PI..=-PI)
+                    .prefix("Beam Direction: ")
+                    .step_by(FRAC_PI_8 as f64)
+            });
+            ui.add({
+                egui::Slider::new(&mut beam.width, FRAC_PI_8..=PI)
+                    .prefix("Beam Width: ")
+                    .step_by(FRAC_PI_8 as f64)
+            });
+        }
+        ui.checkbox(&mut data.is_portable, "Portable");
+        if !data.is_portable {
+            data.path.edit(ui, "wifi");
+        }
     });
 }
 
+/// Minimal signal strength required for downloading.
+pub const CONNECTION_THRESHOLD: f32 = 0.8;
+
+pub fn signal_strength(distance_sq: f32, full_strengh_radius: f32) -> f32 {
+    let distance_sq = distance_sq - full_strengh_radius.powi(2);
+    let distance_sq = distance_sq.max(0.0);
+    1.0 / (1.0 + (0.2 * distance_sq).ln_1p())
+}
+
+/// A factor (`0.0..=1.0`) for the strength of a directional router's signal at `to_client`.
+pub fn beam_falloff(beam: &SignalBeam, to_client: Vec2) -> f32 {
+    if to_client.length_squared() < 1e-6 {
+        return 1.0;
+    }
+    let beam_direction = Vec2::new(beam.direction.cos(), beam.direction.sin());
+    let angle_outside_beam = beam_direction.angle_between(to_client).abs() - 0.5 * beam.width;
+    if angle_outside_beam <= 0.0 {
+        1.0
+    } else {
+        (1.0 - angle_outside_beam / BEAM_EDGE_SOFTNESS).max(SIGNAL_LEFT_OUTSIDE_BEAM)
+    }
+}
+
+fn distance_to_segment(point: Vec2, segment_start: Vec2, segment_end: Vec2) -> f32 {
+    let segment = segment_end - segment_start;
+    let length_sq = segment.length_squared();
+    if length_sq == 0.0 {
+        return point.distance(segment_start);
+    }
+    let t = ((point - segment_start).dot(segment) / length_sq).clamp(0.0, 1.0);
+    point.distance(segment_start + t * segment)
+}
+
 fn update_access_points(
-    mut clients_query: Query<(&GlobalTransform, &mut WifiClient)>,
+    mut clients_query: Query<(Entity, &GlobalTransform, &mut WifiClient)>,
     wifis_query: Query<(Entity, &GlobalTransform, &WifiRouter)>,
+    signal_blockers_query: Query<(&GlobalTransform, &SignalBlockingTiles)>,
+    signal_attenuators_query: Query<(&GlobalTransform, &SignalAttenuator)>,
+    jammers_query: Query<(&GlobalTransform, &SignalJammer)>,
+    faraday_cages_query: Query<(&GlobalTransform, &FaradayCage)>,
+    mut access_point_changed_writer: EventWriter<AccessPointChanged>,
 ) {
-    for (client_transform, mut client) in clients_query.iter_mut() {
-        if let Some((wifi_entity, signal_strength)) = wifis_query
+    for (client_entity, client_transform, mut client) in clients_query.iter_mut() {
+        let previous_access_point = client.access_point;
+        let client_position = client_transform.translation.truncate();
+        let is_in_faraday_cage = faraday_cages_query.iter().any(|(cage_transform, cage)| {
+            cage.contains(cage_transform.translation.truncate(), client_position)
+        });
+        let jamming: f32 = jammers_query
+            .iter()
+            .map(|(jammer_transform, jammer)| {
+                jammer.jamming_at(jammer_transform.translation.truncate(), client_position)
+            })
+            .sum();
+        let best_access_point = wifis_query
             .iter()
+            .filter(|(_, _, wifi_router)| wifi_router.is_online)
             .map(|(wifi_entity, wifi_transform, wifi_router)| {
                 let distance_sq = client_transform
                     .translation
                     .distance_squared(wifi_transform.translation);
-                let distance_sq = distance_sq - wifi_router.full_strengh_radius.powi(2);
-                let distance_sq = distance_sq.max(0.0);
-                let signal_strength = 1.0 / (1.0 + (0.2 * distance_sq).ln_1p());
-                (wifi_entity, signal_strength)
+                let attenuation: f32 = signal_blockers_query
+                    .iter()
+                    .map(|(blocker_transform, signal_blocking_tiles)| {
+                        signal_blocking_tiles.attenuation(
+                            blocker_transform.translation.truncate(),
+                            wifi_transform.translation.truncate(),
+                            client_transform.translation.truncate(),
+                        )
+                    })
+                    .product();
+                let attenuation: f32 = attenuation
+                    * signal_attenuators_query
+                        .iter()
+                        .filter(|(attenuator_transform, signal_attenuator)| {
+                            distance_to_segment(
+                                attenuator_transform.translation.truncate(),
+                                wifi_transform.translation.truncate(),
+                                client_transform.translation.truncate(),
+                            ) < signal_attenuator.radius
+                        })
+                        .map(|(_, signal_attenuator)| signal_attenuator.signal_left)
+                        .product::<f32>();
+                let attenuation = if let Some(beam) = &wifi_router.beam {
+                    attenuation
+                        * beam_falloff(
+                            beam,
+                            (client_transform.translation - wifi_transform.translation).truncate(),
+                        )
+                } else {
+                    attenuation
+                };
+                (
+                    wifi_entity,
+                    attenuation * signal_strength(distance_sq, wifi_router.full_strengh_radius),
+                )
             })
             .max_by_key(|(_, signal_strength)| float_ord::FloatOrd(*signal_strength))
-        {
-            client.access_point = Some(wifi_entity);
-            client.signal_strength = signal_strength;
-        } else {
-            client.access_point = None;
-            client.signal_strength = 0.0;
+            .map(|(wifi_entity, signal_strength)| (wifi_entity, signal_strength - jamming));
+        client.is_jammed = is_in_faraday_cage
+            || (0.0 < jamming
+                && best_access_point.map_or(true, |(_, signal_strength)| signal_strength <= 0.0));
+        match best_access_point {
+            Some((wifi_entity, signal_strength)) if !client.is_jammed => {
+                client.access_point = Some(wifi_entity);
+                client.signal_strength = signal_strength;
+            }
+            _ => {
+                client.access_point = None;
+                client.signal_strength = 0.0;
+            }
+        }
+        if client.access_point != previous_access_point {
+            access_point_changed_writer.send(AccessPointChanged {
+                client: client_entity,
+                previous: previous_access_point,
+                current: client.access_point,
+            });
+        }
+    }
+}
+
+fn update_router_sprites(mut query: Query<(&WifiRouter, &mut Sprite), Changed<WifiRouter>>) {
+    for (wifi_router, mut sprite) in query.iter_mut() {
+        sprite
+            .color
+            .set_a(if wifi_router.is_online { 0.9 } else { 0.2 });
+    }
+}
+
+fn update_beam_cones(
+    routers_query: Query<(&WifiRouter, &Children), Changed<WifiRouter>>,
+    mut beam_cones_query: Query<&mut Transform, With<BeamCone>>,
+) {
+    for (wifi_router, children) in routers_query.iter() {
+        let beam = some_or!(wifi_router.beam; continue);
+        for child in children.iter() {
+            if let Ok(mut transform) = beam_cones_query.get_mut(*child) {
+                transform.rotation = Quat::from_rotation_z(beam.direction);
+            }
         }
     }
 }
 
 fn update_download_progress(
-    time: Res<Time>,
-    mut query: Query<(&WifiClient, &mut DownloadProgress)>,
+    game_clock: Res<GameClock>,
+    mut query: Query<(Entity, &WifiClient, &mut DownloadProgress)>,
+    mut download_started_writer: EventWriter<DownloadStarted>,
+    mut connection_lost_writer: EventWriter<ConnectionLost>,
+    mut reconnected_writer: EventWriter<Reconnected>,
+    mut download_completed_writer: EventWriter<DownloadCompleted>,
 ) {
-    for (wifi_client, mut download_progress) in query.iter_mut() {
-        let connected = 0.8 <= wifi_client.signal_strength;
+    for (client_entity, wifi_client, mut download_progress) in query.iter_mut() {
+        let connected = CONNECTION_THRESHOLD <= wifi_client.signal_strength;
         *download_progress = match *download_progress {
             DownloadProgress::Disconnected => {
                 if connected {
+                    download_started_writer.send(DownloadStarted {
+                        client: client_entity,
+                    });
                     DownloadProgress::Downloading { progress: 0.0 }
                 } else {
                     DownloadProgress::Disconnected
@@ -112,10 +358,14 @@ fn update_download_progress(
                 progress,
             } => {
                 if connected {
+                    reconnected_writer.send(Reconnected {
+                        client: client_entity,
+                        progress,
+                    });
                     DownloadProgress::Downloading { progress }
                 } else {
                     let time_before_disconnection =
-                        time_before_disconnection - time.delta_seconds();
+                        time_before_disconnection - game_clock.delta_seconds();
                     if time_before_disconnection <= 0.0 {
                         DownloadProgress::Disconnected
                     } else {
@@ -128,13 +378,20 @@ fn update_download_progress(
             }
             DownloadProgress::Downloading { progress } => {
                 if connected {
-                    let progress = progress + time.delta_seconds() / 10.0;
+                    let progress = progress + game_clock.delta_seconds() / 10.0;
                     if 1.0 <= progress {
+                        download_completed_writer.send(DownloadCompleted {
+                            client: client_entity,
+                        });
                         DownloadProgress::Completed
                     } else {
                         DownloadProgress::Downloading { progress }
                     }
                 } else {
+                    connection_lost_writer.send(ConnectionLost {
+                        client: client_entity,
+                        progress,
+                    });
                     DownloadProgress::LosingConnection {
                         time_before_disconnection: 5.0,
                         progress,
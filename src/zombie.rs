@@ -1,13 +1,16 @@
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
 use bevy_yoleck::{egui, YoleckEdit, YoleckExtForApp, YoleckPopulate, YoleckTypeHandler};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use crate::global_types::{AppState, Grabbable, IsZombie, WifiClient, WifiRouter};
+use crate::global_types::{AppState, GameSystemLabel, GrabStatus, Grabbable, IsZombie, WifiClient};
 use crate::loading::GameAssets;
 use crate::movement_resolver::MoveController;
-use crate::utils::some_or;
+use crate::zombie_ai::{run_zombie_behaviors, BehaviorNode, ZombieBrain};
+use crate::zombie_perception::ZombiePerception;
 
 pub struct ZombiePlugin;
 
@@ -23,55 +26,138 @@ impl Plugin for ZombiePlugin {
                 }))
                 .edit_with(edit)
         });
-        app.add_system_set(SystemSet::on_update(AppState::Game).with_system(follow_wifi_signal));
+        app.init_resource::<ZombieSteeringSettings>();
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game).with_system(
+                steer_crowd
+                    .after(run_zombie_behaviors)
+                    .before(GameSystemLabel::ApplyMovement),
+            )
+        });
     }
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Zombie {
     #[serde(default)]
-    position: Vec2,
+    pub(crate) position: Vec2,
     #[serde(default)]
-    rotation: f32,
+    pub(crate) rotation: f32,
+    #[serde(default)]
+    pub(crate) kind: ZombieKind,
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ZombieKind {
+    Regular,
+    Runner,
+    Brute,
+}
+
+impl Default for ZombieKind {
+    fn default() -> Self {
+        Self::Regular
+    }
+}
+
+impl ZombieKind {
+    pub const ALL: [ZombieKind; 3] = [ZombieKind::Regular, ZombieKind::Runner, ZombieKind::Brute];
+
+    fn max_speed(&self) -> f32 {
+        match self {
+            ZombieKind::Regular => 1.0,
+            ZombieKind::Runner => 2.0,
+            ZombieKind::Brute => 0.6,
+        }
+    }
+
+    fn density(&self) -> f32 {
+        match self {
+            ZombieKind::Regular => 10.0,
+            ZombieKind::Runner => 6.0,
+            ZombieKind::Brute => 30.0,
+        }
+    }
+
+    fn tint(&self) -> Color {
+        match self {
+            ZombieKind::Regular => Color::WHITE,
+            ZombieKind::Runner => Color::rgb(1.0, 1.0, 0.6),
+            ZombieKind::Brute => Color::rgb(1.0, 0.6, 0.6),
+        }
+    }
+}
+
+pub fn zombie_kind_combo_box(ui: &mut egui::Ui, id_source: &str, kind: &mut ZombieKind) {
+    egui::ComboBox::from_id_source(id_source)
+        .selected_text(format!("Kind: {:?}", kind))
+        .show_ui(ui, |ui| {
+            for option in ZombieKind::ALL {
+                ui.selectable_value(kind, option, format!("{:?}", option));
+            }
+        });
 }
 
 fn populate(mut populate: YoleckPopulate<Zombie>, game_assets: Res<GameAssets>) {
     populate.populate(|_ctx, data, mut cmd| {
-        cmd.insert(IsZombie);
-        cmd.insert_bundle(SpriteBundle {
-            sprite: Sprite {
-                custom_size: Some(Vec2::new(1.0, 1.0)),
-                ..Default::default()
-            },
-            texture: game_assets.zombie.clone(),
-            ..Default::default()
-        });
-        cmd.insert_bundle(TransformBundle::from_transform(
-            Transform::from_translation(data.position.extend(0.0))
-                .with_rotation(Quat::from_rotation_z(data.rotation)),
-        ));
-        cmd.insert(RigidBody::Dynamic);
-        cmd.insert(Damping {
-            linear_damping: 1.0,
-            angular_damping: 1.0,
-        });
-        cmd.insert(Collider::cuboid(0.4, 0.2));
-        cmd.insert(ColliderMassProperties::Density(10.0));
-        cmd.insert(Velocity::default());
-        cmd.insert(MoveController {
-            max_speed: 1.0,
-            impulse_coefficient: 100.0,
+        setup_zombie(
+            &mut cmd,
+            &game_assets,
+            data.kind,
+            data.position,
+            data.rotation,
+        );
+    });
+}
+
+pub fn setup_zombie(
+    cmd: &mut EntityCommands,
+    game_assets: &GameAssets,
+    kind: ZombieKind,
+    position: Vec2,
+    rotation: f32,
+) {
+    cmd.insert(IsZombie);
+    cmd.insert(kind);
+    cmd.insert_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: kind.tint(),
+            custom_size: Some(Vec2::new(1.0, 1.0)),
             ..Default::default()
-        });
-        cmd.insert(WifiClient::default());
-        cmd.insert(Grabbable);
-        cmd.insert(ActiveEvents::COLLISION_EVENTS);
+        },
+        texture: game_assets.zombie.clone(),
+        ..Default::default()
     });
+    cmd.insert_bundle(TransformBundle::from_transform(
+        Transform::from_translation(position.extend(0.0))
+            .with_rotation(Quat::from_rotation_z(rotation)),
+    ));
+    cmd.insert(RigidBody::Dynamic);
+    cmd.insert(Damping {
+        linear_damping: 1.0,
+        angular_damping: 1.0,
+    });
+    cmd.insert(Collider::cuboid(0.4, 0.2));
+    cmd.insert(ColliderMassProperties::Density(kind.density()));
+    cmd.insert(Velocity::default());
+    cmd.insert(MoveController {
+        max_speed: kind.max_speed(),
+        impulse_coefficient: 100.0,
+        ..Default::default()
+    });
+    cmd.insert(WifiClient::default());
+    cmd.insert(ZombieBrain::default());
+    cmd.insert(ZombiePerception::default());
+    cmd.insert(GrabStatus::NoGrab);
+    cmd.insert(SpeedJitter(rand::thread_rng().gen_range(0.0..=1.0)));
+    cmd.insert(Grabbable::default());
+    cmd.insert(ActiveEvents::COLLISION_EVENTS);
 }
 
 fn edit(mut edit: YoleckEdit<Zombie>) {
     edit.edit(|_, data, ui| {
         use std::f32::consts::{FRAC_PI_8, PI};
+        zombie_kind_combo_box(ui, "zombie-kind", &mut data.kind);
         ui.add({
             egui::Slider::new(&mut data.rotation, PI..=-PI)
                 .prefix("Angle: ")
@@ -80,21 +166,80 @@ fn edit(mut edit: YoleckEdit<Zombie>) {
     });
 }
 
-fn follow_wifi_signal(
-    mut zombies_query: Query<(&GlobalTransform, &WifiClient, &mut MoveController), With<IsZombie>>,
-    wifi_query: Query<&GlobalTransform, With<WifiRouter>>,
+/// Tunable values for how zombies move around.
+pub struct ZombieSteeringSettings {
+    /// Zombies closer than this push each other away.
+    pub separation_radius: f32,
+    pub separation_weight: f32,
+    /// Each zombie moves at a random fraction of its max speed, from `1.0 - speed_jitter` to `1.0`.
+    pub speed_jitter: f32,
+}
+
+impl Default for ZombieSteeringSettings {
+    fn default() -> Self {
+        Self {
+            separation_radius: 1.0,
+            separation_weight: 1.5,
+            speed_jitter: 0.25,
+        }
+    }
+}
+
+/// Picked randomly for each zombie (`0.0..=1.0`), so that a crowd doesn't move in lockstep. The
+/// actual slowdown is scaled by `ZombieSteeringSettings::speed_jitter`.
+#[derive(Component)]
+struct SpeedJitter(f32);
+
+/// Adjusts the speed picked by the zombie's behavior so that zombies don't pile up on each other.
+#[allow(clippy::type_complexity)]
+pub fn steer_crowd(
+    settings: Res<ZombieSteeringSettings>,
+    mut zombies_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut MoveController,
+            &SpeedJitter,
+            &ZombieBrain,
+        ),
+        With<IsZombie>,
+    >,
 ) {
-    for (zombie_transform, wifi_client, mut move_controller) in zombies_query.iter_mut() {
+    let zombie_positions: Vec<(Entity, Vec2)> = zombies_query
+        .iter()
+        .map(|(entity, transform, ..)| (entity, transform.translation.truncate()))
+        .collect();
+    for (zombie_entity, zombie_transform, mut move_controller, speed_jitter, brain) in
+        zombies_query.iter_mut()
+    {
+        // Zombies that should stay put must not be pushed around by the crowd.
+        if matches!(
+            brain.current_node,
+            Some(BehaviorNode::Stunned | BehaviorNode::Idle)
+        ) {
+            continue;
+        }
         let zombie_position = zombie_transform.translation.truncate();
-        let wifi_entity = some_or!(wifi_client.access_point; continue);
-        let closest_wifi_position = some_or!(wifi_query.get(wifi_entity).ok(); continue)
-            .translation
-            .truncate();
-        let vec_to_wifi = closest_wifi_position - zombie_position;
-        if vec_to_wifi.length_squared() < 1.0 {
-            move_controller.target_speed = vec_to_wifi;
-        } else {
-            move_controller.target_speed = vec_to_wifi.normalize();
+        let separation: Vec2 = zombie_positions
+            .iter()
+            .filter(|(other_entity, _)| *other_entity != zombie_entity)
+            .filter_map(|(_, other_position)| {
+                let away = zombie_position - *other_position;
+                let distance = away.length();
+                if 0.0 < distance && distance < settings.separation_radius {
+                    Some(away / distance * (1.0 - distance / settings.separation_radius))
+                } else {
+                    None
+                }
+            })
+            .fold(Vec2::ZERO, |sum, push| sum + push);
+
+        let mut target_speed =
+            move_controller.target_speed + settings.separation_weight * separation;
+        if 1.0 < target_speed.length_squared() {
+            target_speed = target_speed.normalize();
         }
+        move_controller.target_speed =
+            (1.0 - settings.speed_jitter * speed_jitter.0) * target_speed;
     }
 }
@@ -0,0 +1,385 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::game_clock::GameClock;
+use crate::global_types::{
+    AppState, GrabStarted, GrabStatus, Grabbable, IsZombie, WifiClient, WifiRouter,
+};
+use crate::hazard_zone::HazardZoneStatus;
+use crate::loading::GameAssets;
+use crate::movement_resolver::MoveController;
+use crate::player::{holding_joint, spawn_hands};
+use crate::zombie::ZombieKind;
+use crate::zombie_perception::{perceive_player, ZombiePerception, ZombiePerceptionSettings};
+
+pub struct ZombieAiPlugin;
+
+impl Plugin for ZombieAiPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ZombieBehaviorTrees::from_json(include_str!(
+            "../assets/zombie_behaviors.json"
+        )));
+        app.init_resource::<ZombieAiSettings>();
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game)
+                .with_system(run_zombie_behaviors.after(perceive_player))
+        });
+        #[cfg(debug_assertions)]
+        app.add_system(show_zombie_behavior_nodes);
+        #[cfg(debug_assertions)]
+        app.add_system(show_zombie_tuning_window);
+    }
+}
+
+/// A single behavior a zombie can do. The behavior tree of each zombie kind is a prioritized list
+/// of nodes - every frame, the first node that can run is the one that runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BehaviorNode {
+    /// Caught in a stun hazard zone - stand still. Being dragged by the player is not a stun, so
+    /// that dragged zombies keep pulling toward their router.
+    Stunned,
+    /// Run away from the player after being dragged.
+    Flee,
+    /// Grab the player after being dragged, and drag them along to the router.
+    GrabPlayer,
+    /// Go after a player who has signal on their phone.
+    ChasePlayer,
+    SeekRouter,
+    /// Randomly stumble around. Always runs.
+    Wander,
+    /// Stand still. Always runs.
+    Idle,
+}
+
+/// Loaded from `assets/zombie_behaviors.json`.
+#[derive(Deserialize)]
+pub struct ZombieBehaviorTrees(HashMap<ZombieKind, Vec<BehaviorNode>>);
+
+impl ZombieBehaviorTrees {
+    fn from_json(json: &str) -> Self {
+        serde_json::from_str(json).expect("zombie_behaviors.json should be valid")
+    }
+
+    fn tree_for(&self, kind: ZombieKind) -> &[BehaviorNode] {
+        self.0
+            .get(&kind)
+            .map_or(&[BehaviorNode::Idle], Vec::as_slice)
+    }
+}
+
+/// Tunable values for zombie behaviors.
+pub struct ZombieAiSettings {
+    /// Relative to the zombie's max speed.
+    pub idle_wander_speed: f32,
+    /// Relative to the zombie's max speed. Jammed zombies stumble around faster than idle ones.
+    pub confused_wander_speed: f32,
+    /// Range of seconds between picking new wandering directions.
+    pub wander_turn_interval: (f32, f32),
+}
+
+impl Default for ZombieAiSettings {
+    fn default() -> Self {
+        Self {
+            idle_wander_speed: 0.2,
+            confused_wander_speed: 0.5,
+            wander_turn_interval: (0.5, 1.5),
+        }
+    }
+}
+
+#[derive(Component, Default)]
+pub struct ZombieBrain {
+    pub current_node: Option<BehaviorNode>,
+    wander_direction: Vec2,
+    wander_time_until_turn: f32,
+}
+
+/// Everything a node needs to know to decide whether it can run and where to go.
+struct BehaviorContext<'a> {
+    perception: &'a ZombiePerception,
+    is_stunned: bool,
+    is_jammed: bool,
+    can_grab: bool,
+    is_holding_player: bool,
+    /// From the zombie, `None` if there is no router to follow.
+    router_offset: Option<Vec2>,
+}
+
+impl BehaviorNode {
+    fn can_run(&self, ctx: &BehaviorContext, settings: &ZombiePerceptionSettings) -> bool {
+        match self {
+            BehaviorNode::Stunned => ctx.is_stunned,
+            BehaviorNode::Flee => ctx.perception.is_provoked(),
+            BehaviorNode::GrabPlayer => {
+                ctx.can_grab
+                    && (ctx.is_holding_player
+                        || (ctx.perception.is_provoked() && ctx.perception.sees_player))
+            }
+            BehaviorNode::ChasePlayer => {
+                ctx.perception.sees_player
+                    && settings.chase_signal_threshold <= ctx.perception.player_signal_strength
+            }
+            BehaviorNode::SeekRouter => ctx.router_offset.is_some(),
+            BehaviorNode::Wander | BehaviorNode::Idle => true,
+        }
+    }
+}
+
+fn seek(offset: Vec2) -> Vec2 {
+    if offset.length_squared() < 1.0 {
+        offset
+    } else {
+        offset.normalize()
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn run_zombie_behaviors(
+    game_clock: Res<GameClock>,
+    trees: Res<ZombieBehaviorTrees>,
+    settings: Res<ZombieAiSettings>,
+    perception_settings: Res<ZombiePerceptionSettings>,
+    mut zombies_query: Query<
+        (
+            Entity,
+            &ZombieKind,
+            &GlobalTransform,
+            &WifiClient,
+            &mut MoveController,
+            &mut ZombieBrain,
+            &mut ZombiePerception,
+            Option<&mut GrabStatus>,
+        ),
+        With<IsZombie>,
+    >,
+    hazard_zones_query: Query<&HazardZoneStatus>,
+    wifi_query: Query<&GlobalTransform, With<WifiRouter>>,
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut grab_started_writer: EventWriter<GrabStarted>,
+) {
+    let mut rng = rand::thread_rng();
+    for (
+        zombie_entity,
+        kind,
+        zombie_transform,
+        wifi_client,
+        mut move_controller,
+        mut brain,
+        mut perception,
+        mut grab_status,
+    ) in zombies_query.iter_mut()
+    {
+        let zombie_position = zombie_transform.translation.truncate();
+        let ctx = BehaviorContext {
+            perception: &perception,
+            is_stunned: hazard_zones_query
+                .iter()
+                .any(|zone_status| zone_status.stuns(zombie_entity)),
+            is_jammed: wifi_client.is_jammed,
+            can_grab: grab_status.is_some(),
+            is_holding_player: matches!(
+                grab_status.as_deref(),
+                Some(GrabStatus::Holding { other, .. }) if Some(*other) == perception.player
+            ),
+            router_offset: wifi_client
+                .access_point
+                .filter(|_| !wifi_client.is_jammed)
+                .and_then(|wifi_entity| wifi_query.get(wifi_entity).ok())
+                .map(|wifi_transform| wifi_transform.translation.truncate() - zombie_position),
+        };
+        let node = trees
+            .tree_for(*kind)
+            .iter()
+            .copied()
+            .find(|node| node.can_run(&ctx, &perception_settings))
+            .unwrap_or(BehaviorNode::Idle);
+        let BehaviorContext {
+            is_jammed,
+            is_holding_player,
+            router_offset,
+            ..
+        } = ctx;
+
+        if node != BehaviorNode::Wander {
+            brain.wander_time_until_turn = 0.0;
+        }
+        let direction_to_player = perception.player_offset.normalize_or_zero();
+        move_controller.target_speed = match node {
+            BehaviorNode::Stunned | BehaviorNode::Idle => Vec2::ZERO,
+            BehaviorNode::Flee => -direction_to_player,
+            BehaviorNode::GrabPlayer if is_holding_player => router_offset.map_or(Vec2::ZERO, seek),
+            BehaviorNode::GrabPlayer => {
+                let is_in_reach = perception.player_offset.length()
+                    <= perception_settings.grab_reach
+                    && perception.grab_cooldown_left <= 0.0;
+                if let (Some(player_entity), Some(grab_status)) =
+                    (perception.player, grab_status.as_deref_mut())
+                {
+                    if is_in_reach && matches!(grab_status, GrabStatus::NoGrab) {
+                        // The player is not `Grabbable`, so they are held the default way.
+                        let hands_entity = spawn_hands(&mut commands, &game_assets, zombie_entity);
+                        commands
+                            .entity(hands_entity)
+                            .insert(holding_joint(player_entity, &Grabbable::default()));
+                        *grab_status = GrabStatus::Holding {
+                            hands_entity,
+                            other: player_entity,
+                        };
+                        perception.grab_time_left = perception_settings.grab_duration;
+                        grab_started_writer.send(GrabStarted {
+                            grabber: zombie_entity,
+                            grabbed: player_entity,
+                        });
+                    }
+                }
+                direction_to_player
+            }
+            BehaviorNode::ChasePlayer => direction_to_player,
+            BehaviorNode::SeekRouter => router_offset.map_or(Vec2::ZERO, seek),
+            BehaviorNode::Wander => {
+                brain.wander_time_until_turn -= game_clock.delta_seconds();
+                if brain.wander_time_until_turn <= 0.0 {
+                    let angle = rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI);
+                    brain.wander_direction = Vec2::new(angle.cos(), angle.sin());
+                    let (min_interval, max_interval) = settings.wander_turn_interval;
+                    brain.wander_time_until_turn = rng.gen_range(min_interval..=max_interval);
+                }
+                let wander_speed = if is_jammed {
+                    settings.confused_wander_speed
+                } else {
+                    settings.idle_wander_speed
+                };
+                wander_speed * brain.wander_direction
+            }
+        };
+        brain.current_node = Some(node);
+    }
+}
+
+/// Debug builds only - F8 toggles labels with the current behavior node of each zombie.
+#[cfg(debug_assertions)]
+fn show_zombie_behavior_nodes(
+    keyboard: Res<Input<KeyCode>>,
+    mut is_shown: Local<bool>,
+    mut egui_context: ResMut<bevy_egui::EguiContext>,
+    windows: Res<Windows>,
+    images: Res<Assets<Image>>,
+    cameras_query: Query<(&Camera, &GlobalTransform)>,
+    zombies_query: Query<(Entity, &GlobalTransform, &ZombieBrain)>,
+) {
+    use bevy_egui::egui;
+
+    use crate::utils::some_or;
+
+    if keyboard.just_pressed(KeyCode::F8) {
+        *is_shown = !*is_shown;
+    }
+    if !*is_shown {
+        return;
+    }
+    let (camera, camera_transform) = some_or!(cameras_query.iter().next(); return);
+    let window_height = some_or!(windows.get_primary(); return).height();
+    for (zombie_entity, zombie_transform, brain) in zombies_query.iter() {
+        let node = some_or!(brain.current_node; continue);
+        let screen_position = some_or!(
+            camera.world_to_screen(&windows, &images, camera_transform, zombie_transform.translation);
+            continue
+        );
+        egui::Area::new(("zombie-behavior-node", zombie_entity))
+            .fixed_pos([screen_position.x, window_height - screen_position.y])
+            .interactable(false)
+            .show(egui_context.ctx_mut(), |ui| {
+                ui.label(
+                    egui::RichText::new(format!("{:?}", node))
+                        .small()
+                        .color(egui::Color32::YELLOW)
+                        .background_color(egui::Color32::BLACK),
+                );
+            });
+    }
+}
+
+/// Debug builds only - F7 toggles a window for tuning how zombies move and behave while playing.
+#[cfg(debug_assertions)]
+fn show_zombie_tuning_window(
+    keyboard: Res<Input<KeyCode>>,
+    mut is_shown: Local<bool>,
+    mut egui_context: ResMut<bevy_egui::EguiContext>,
+    mut steering_settings: ResMut<crate::zombie::ZombieSteeringSettings>,
+    mut perception_settings: ResMut<ZombiePerceptionSettings>,
+    mut ai_settings: ResMut<ZombieAiSettings>,
+) {
+    use bevy_egui::egui;
+
+    if keyboard.just_pressed(KeyCode::F7) {
+        *is_shown = !*is_shown;
+    }
+    if !*is_shown {
+        return;
+    }
+    egui::Window::new("Zombie Tuning").show(egui_context.ctx_mut(), |ui| {
+        ui.heading("Steering");
+        ui.add(
+            egui::Slider::new(&mut steering_settings.separation_radius, 0.0..=3.0)
+                .text("Separation Radius"),
+        );
+        ui.add(
+            egui::Slider::new(&mut steering_settings.separation_weight, 0.0..=5.0)
+                .text("Separation Weight"),
+        );
+        ui.add(
+            egui::Slider::new(&mut steering_settings.speed_jitter, 0.0..=1.0).text("Speed Jitter"),
+        );
+
+        ui.heading("Perception");
+        ui.add(
+            egui::Slider::new(&mut perception_settings.sight_radius, 0.0..=20.0)
+                .text("Sight Radius"),
+        );
+        ui.add(
+            egui::Slider::new(
+                &mut perception_settings.sight_cone,
+                0.0..=std::f32::consts::TAU,
+            )
+            .text("Sight Cone"),
+        );
+        ui.add(
+            egui::Slider::new(&mut perception_settings.chase_signal_threshold, 0.0..=1.0)
+                .text("Chase Signal Threshold"),
+        );
+        ui.add(
+            egui::Slider::new(&mut perception_settings.provoked_duration, 0.0..=20.0)
+                .text("Provoked Duration"),
+        );
+        ui.add(
+            egui::Slider::new(&mut perception_settings.grab_reach, 0.0..=3.0).text("Grab Reach"),
+        );
+        ui.add(
+            egui::Slider::new(&mut perception_settings.grab_duration, 0.0..=10.0)
+                .text("Grab Duration"),
+        );
+        ui.add(
+            egui::Slider::new(&mut perception_settings.grab_cooldown, 0.0..=20.0)
+                .text("Grab Cooldown"),
+        );
+
+        ui.heading("Behaviors");
+        ui.add(
+            egui::Slider::new(&mut ai_settings.idle_wander_speed, 0.0..=1.0)
+                .text("Idle Wander Speed"),
+        );
+        ui.add(
+            egui::Slider::new(&mut ai_settings.confused_wander_speed, 0.0..=1.0)
+                .text("Confused Wander Speed"),
+        );
+        let (min_interval, max_interval) = &mut ai_settings.wander_turn_interval;
+        ui.add(egui::Slider::new(min_interval, 0.1..=5.0).text("Min Wander Turn Interval"));
+        ui.add(egui::Slider::new(max_interval, 0.1..=5.0).text("Max Wander Turn Interval"));
+        if *max_interval < *min_interval {
+            *max_interval = *min_interval;
+        }
+    });
+}
@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{InteractionGroups, RapierContext, Sensor};
+
+use crate::game_clock::GameClock;
+use crate::global_types::{AppState, GrabReleased, GrabStatus, IsPlayer, WifiClient};
+use crate::level_info::ZombiesPerceivePlayer;
+use crate::utils::some_or;
+
+pub struct ZombiePerceptionPlugin;
+
+impl Plugin for ZombiePerceptionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ZombiePerceptionSettings>();
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game)
+                .with_system(provoke_dragged_zombies)
+                .with_system(perceive_player.after(provoke_dragged_zombies))
+                .with_system(release_zombie_grabs)
+        });
+    }
+}
+
+/// Tunable values for how zombies notice and react to the player.
+pub struct ZombiePerceptionSettings {
+    pub sight_radius: f32,
+    /// Full angular width of the sight cone, in radians.
+    pub sight_cone: f32,
+    /// Zombies that chase only notice players with at least this much signal.
+    pub chase_signal_threshold: f32,
+    /// How long a zombie stays upset after being dragged.
+    pub provoked_duration: f32,
+    pub grab_reach: f32,
+    pub grab_duration: f32,
+    pub grab_cooldown: f32,
+}
+
+impl Default for ZombiePerceptionSettings {
+    fn default() -> Self {
+        Self {
+            sight_radius: 6.0,
+            sight_cone: 2.0 * std::f32::consts::FRAC_PI_3,
+            chase_signal_threshold: 0.5,
+            provoked_duration: 4.0,
+            grab_reach: 1.2,
+            grab_duration: 1.5,
+            grab_cooldown: 3.0,
+        }
+    }
+}
+
+/// What a zombie knows about the player. Only updated in levels where zombies perceive the player.
+#[derive(Component, Default)]
+pub struct ZombiePerception {
+    pub player: Option<Entity>,
+    pub sees_player: bool,
+    /// From the zombie to the player.
+    pub player_offset: Vec2,
+    pub player_signal_strength: f32,
+    provoked_time_left: f32,
+    pub grab_time_left: f32,
+    pub grab_cooldown_left: f32,
+}
+
+impl ZombiePerception {
+    pub fn is_provoked(&self) -> bool {
+        0.0 < self.provoked_time_left
+    }
+}
+
+fn provoke_dragged_zombies(
+    settings: Res<ZombiePerceptionSettings>,
+    enabled_query: Query<(), With<ZombiesPerceivePlayer>>,
+    mut grab_released_reader: EventReader<GrabReleased>,
+    player_query: Query<(), With<IsPlayer>>,
+    mut zombies_query: Query<&mut ZombiePerception>,
+) {
+    for event in grab_released_reader.iter() {
+        if enabled_query.is_empty() || player_query.get(event.grabber).is_err() {
+            continue;
+        }
+        if let Ok(mut perception) = zombies_query.get_mut(event.grabbed) {
+            perception.provoked_time_left = settings.provoked_duration;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn perceive_player(
+    game_clock: Res<GameClock>,
+    settings: Res<ZombiePerceptionSettings>,
+    rapier_context: Res<RapierContext>,
+    enabled_query: Query<(), With<ZombiesPerceivePlayer>>,
+    player_query: Query<(Entity, &GlobalTransform, &WifiClient), With<IsPlayer>>,
+    mut zombies_query: Query<(Entity, &GlobalTransform, &mut ZombiePerception)>,
+    sensors_query: Query<&Sensor>,
+) {
+    if enabled_query.is_empty() {
+        return;
+    }
+    let (player_entity, player_transform, player_wifi_client) =
+        some_or!(player_query.get_single().ok(); return);
+    let player_position = player_transform.translation.truncate();
+    let delta = game_clock.delta_seconds();
+
+    for (zombie_entity, zombie_transform, mut perception) in zombies_query.iter_mut() {
+        perception.provoked_time_left = (perception.provoked_time_left - delta).max(0.0);
+        perception.grab_time_left = (perception.grab_time_left - delta).max(0.0);
+        perception.grab_cooldown_left = (perception.grab_cooldown_left - delta).max(0.0);
+
+        let zombie_position = zombie_transform.translation.truncate();
+        let to_player = player_position - zombie_position;
+        let distance = to_player.length();
+        let forward = (zombie_transform.rotation * Vec3::Y).truncate();
+        // Sensors (like hands and zones) don't block sight.
+        let blocks_sight = |entity: Entity| {
+            entity != zombie_entity && sensors_query.get(entity).map_or(true, |sensor| !sensor.0)
+        };
+        perception.player = Some(player_entity);
+        perception.player_offset = to_player;
+        perception.player_signal_strength = player_wifi_client.signal_strength;
+        perception.sees_player = distance <= settings.sight_radius
+            && forward.angle_between(to_player).abs() <= 0.5 * settings.sight_cone
+            && rapier_context
+                .cast_ray(
+                    zombie_position,
+                    to_player.normalize_or_zero(),
+                    distance,
+                    true,
+                    InteractionGroups::all(),
+                    Some(&blocks_sight as &dyn Fn(Entity) -> bool),
+                )
+                .map_or(false, |(hit_entity, _)| hit_entity == player_entity);
+    }
+}
+
+/// Zombies don't hold on to the player forever, and don't keep a grab button pressed.
+fn release_zombie_grabs(
+    settings: Res<ZombiePerceptionSettings>,
+    mut zombies_query: Query<(Entity, &mut ZombiePerception, &mut GrabStatus)>,
+    mut commands: Commands,
+    mut grab_released_writer: EventWriter<GrabReleased>,
+) {
+    for (zombie_entity, mut perception, mut grab_status) in zombies_query.iter_mut() {
+        match *grab_status {
+            GrabStatus::NoGrab => {}
+            GrabStatus::GrabFailed => {
+                *grab_status = GrabStatus::NoGrab;
+                perception.grab_cooldown_left = settings.grab_cooldown;
+            }
+            GrabStatus::Reaching { hands_entity, .. } => {
+                commands.entity(hands_entity).despawn_recursive();
+                *grab_status = GrabStatus::NoGrab;
+            }
+            GrabStatus::Holding {
+                hands_entity,
+                other,
+            } => {
+                if perception.grab_time_left <= 0.0 {
+                    commands.entity(hands_entity).despawn_recursive();
+                    grab_released_writer.send(GrabReleased {
+                        grabber: zombie_entity,
+                        grabbed: other,
+                    });
+                    *grab_status = GrabStatus::NoGrab;
+                    perception.grab_cooldown_left = settings.grab_cooldown;
+                    perception.provoked_time_left = 0.0;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use bevy::prelude::*;
+use bevy_yoleck::vpeol_2d::{yoleck_vpeol_position_edit_adapter, YoleckVpeolTransform2dProjection};
+use bevy_yoleck::{egui, YoleckEdit, YoleckExtForApp, YoleckPopulate, YoleckTypeHandler};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::game_clock::GameClock;
+use crate::global_types::{AppState, DownloadProgress, IsPlayer, IsZombie, SpawnedDuringLevel};
+use crate::loading::GameAssets;
+use crate::zombie::{setup_zombie, zombie_kind_combo_box, ZombieKind};
+
+pub struct ZombieSpawnerPlugin;
+
+impl Plugin for ZombieSpawnerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_handler({
+            YoleckTypeHandler::<ZombieSpawner>::new("ZombieSpawner")
+                .populate_with(populate)
+                .with(yoleck_vpeol_position_edit_adapter(
+                    |zombie_spawner: &mut ZombieSpawner| YoleckVpeolTransform2dProjection {
+                        translation: &mut zombie_spawner.position,
+                    },
+                ))
+                .edit_with(edit)
+        });
+        app.add_system_set({
+            SystemSet::on_update(AppState::Game)
+                .with_system(activate_spawners)
+                .with_system(spawn_zombies.after(activate_spawners))
+        });
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ZombieSpawner {
+    #[serde(default)]
+    pub(crate) position: Vec2,
+    #[serde(default = "default_spawn_interval")]
+    spawn_interval: f32,
+    #[serde(default = "default_max_alive")]
+    max_alive: usize,
+    #[serde(default)]
+    kind: ZombieKind,
+    #[serde(default)]
+    activation: SpawnerActivation,
+}
+
+fn default_spawn_interval() -> f32 {
+    3.0
+}
+
+fn default_max_alive() -> usize {
+    5
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum SpawnerActivation {
+    LevelStart,
+    /// The zone is relative to the spawner's position.
+    PlayerInZone {
+        offset: Vec2,
+        size: Vec2,
+    },
+    DownloadProgress {
+        at_least: f32,
+    },
+}
+
+impl Default for SpawnerActivation {
+    fn default() -> Self {
+        Self::LevelStart
+    }
+}
+
+#[derive(Component)]
+pub struct ZombieSpawnerStatus {
+    pub kind: ZombieKind,
+    spawn_interval: f32,
+    max_alive: usize,
+    activation: SpawnerActivation,
+    is_active: bool,
+    time_to_next_spawn: f32,
+    spawned: Vec<Entity>,
+}
+
+fn populate(mut populate: YoleckPopulate<ZombieSpawner>) {
+    populate.populate(|ctx, data, mut cmd| {
+        cmd.despawn_descendants();
+        cmd.insert(ZombieSpawnerStatus {
+            kind: data.kind,
+            spawn_interval: data.spawn_interval,
+            max_alive: data.max_alive,
+            activation: data.activation.clone(),
+            is_active: false,
+            time_to_next_spawn: 0.0,
+            spawned: Vec::new(),
+        });
+        cmd.insert_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.3, 0.0, 0.0, 0.5),
+                custom_size: Some(Vec2::new(1.0, 1.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(data.position.extend(-0.2)),
+            ..Default::default()
+        });
+        if ctx.is_in_editor() {
+            if let SpawnerActivation::PlayerInZone { offset, size } = data.activation {
+                cmd.with_children(|commands| {
+                    commands.spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgba(0.6, 0.0, 0.0, 0.2),
+                            custom_size: Some(size),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_translation(offset.extend(-0.1)),
+                        ..Default::default()
+                    });
+                });
+            }
+        }
+    });
+}
+
+fn edit(mut edit: YoleckEdit<ZombieSpawner>) {
+    edit.edit(|_ctx, data, ui| {
+        zombie_kind_combo_box(ui, "zombie-spawner-kind", &mut data.kind);
+        ui.add(
+            egui::Slider::new(&mut data.spawn_interval, 0.5..=30.0)
+                .logarithmic(true)
+                .prefix("Spawn Every: ")
+                .suffix("s"),
+        );
+        ui.add(egui::Slider::new(&mut data.max_alive, 0..=20).prefix("Max Alive: "));
+        ui.horizontal(|ui| {
+            ui.label("Activation:");
+            for (label, is_selected, activation) in [
+                (
+                    "Level Start",
+                    matches!(data.activation, SpawnerActivation::LevelStart),
+                    SpawnerActivation::LevelStart,
+                ),
+                (
+                    "Player In Zone",
+                    matches!(data.activation, SpawnerActivation::PlayerInZone { .. }),
+                    SpawnerActivation::PlayerInZone {
+                        offset: Vec2::ZERO,
+                        size: Vec2::new(2.0, 2.0),
+                    },
+                ),
+                (
+                    "Download Progress",
+                    matches!(data.activation, SpawnerActivation::DownloadProgress { .. }),
+                    SpawnerActivation::DownloadProgress { at_least: 0.5 },
+                ),
+            ] {
+                if ui.selectable_label(is_selected, label).clicked() && !is_selected {
+                    data.activation = activation;
+                }
+            }
+        });
+        match &mut data.activation {
+            SpawnerActivation::LevelStart => {}
+            SpawnerActivation::PlayerInZone { offset, size } => {
+                for (label, vec) in [("Zone Offset", offset), ("Zone Size", size)] {
+                    ui.horizontal(|ui| {
+                        ui.label(label);
+                        ui.add(egui::DragValue::new(&mut vec.x).prefix("X:").speed(0.05));
+                        ui.add(egui::DragValue::new(&mut vec.y).prefix("Y:").speed(0.05));
+                    });
+                }
+            }
+            SpawnerActivation::DownloadProgress { at_least } => {
+                ui.add(egui::Slider::new(at_least, 0.0..=1.0).prefix("Download At Least: "));
+            }
+        }
+    });
+}
+
+fn activate_spawners(
+    mut spawners_query: Query<(&GlobalTransform, &mut ZombieSpawnerStatus)>,
+    players_query: Query<(&GlobalTransform, &DownloadProgress), With<IsPlayer>>,
+) {
+    for (spawner_transform, mut spawner_status) in spawners_query.iter_mut() {
+        if spawner_status.is_active {
+            continue;
+        }
+        spawner_status.is_active = match spawner_status.activation {
+            SpawnerActivation::LevelStart => true,
+            SpawnerActivation::PlayerInZone { offset, size } => {
+                let zone_center = spawner_transform.translation.truncate() + offset;
+                players_query.iter().any(|(player_transform, _)| {
+                    let vec = player_transform.translation.truncate() - zone_center;
+                    vec.x.abs() <= 0.5 * size.x && vec.y.abs() <= 0.5 * size.y
+                })
+            }
+            SpawnerActivation::DownloadProgress { at_least } => players_query
+                .iter()
+                .any(|(_, download_progress)| at_least <= download_progress.progress()),
+        };
+    }
+}
+
+fn spawn_zombies(
+    game_clock: Res<GameClock>,
+    mut spawners_query: Query<(&GlobalTransform, &mut ZombieSpawnerStatus)>,
+    zombies_query: Query<(), With<IsZombie>>,
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+) {
+    for (spawner_transform, mut spawner_status) in spawners_query.iter_mut() {
+        if !spawner_status.is_active {
+            continue;
+        }
+        spawner_status
+            .spawned
+            .retain(|&entity| zombies_query.get(entity).is_ok());
+        spawner_status.time_to_next_spawn -= game_clock.delta_seconds();
+        if 0.0 < spawner_status.time_to_next_spawn
+            || spawner_status.max_alive <= spawner_status.spawned.len()
+        {
+            continue;
+        }
+        spawner_status.time_to_next_spawn = spawner_status.spawn_interval;
+        let zombie_entity = spawn_zombie_at(
+            &mut commands,
+            &game_assets,
+            spawner_status.kind,
+            spawner_transform.translation.truncate(),
+        );
+        spawner_status.spawned.push(zombie_entity);
+    }
+}
+
+pub fn spawn_zombie_at(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    kind: ZombieKind,
+    spawner_position: Vec2,
+) -> Entity {
+    let mut rng = rand::thread_rng();
+    let offset = Vec2::new(rng.gen_range(-0.3..=0.3), rng.gen_range(-0.3..=0.3));
+    let rotation = rng.gen_range(-std::f32::consts::PI..std::f32::consts::PI);
+    let mut cmd = commands.spawn();
+    setup_zombie(
+        &mut cmd,
+        game_assets,
+        kind,
+        spawner_position + offset,
+        rotation,
+    );
+    cmd.insert(SpawnedDuringLevel);
+    cmd.id()
+}